@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::get_players;
+use crate::filter_presets::resolve_filters;
+use crate::model::{
+    BalanceAudit, BalanceIssue, BalanceSpec, BalanceTarget, BandTarget, GroupDimension, Player,
+    PlayerFilters, ValueFilter,
+};
+use crate::player_dimensions::{GroupValue, DEFAULT_BAND_WIDTH};
+use crate::player_filters::filtered_ids;
+
+/// Most groups a single target may expand to, across all its dimensions
+const MAX_EXPECTED_GROUPS: usize = 10_000;
+
+fn read_spec(path: &str) -> Result<BalanceSpec, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid target spec {}: {}", path, e))
+}
+
+/// Values of `dimension` the target's filters explicitly ask for
+fn listed_values(dimension: GroupDimension, filters: Option<&PlayerFilters>) -> Result<Vec<GroupValue>, String> {
+    let f = match filters {
+        Some(f) => f,
+        None => return Ok(Vec::new()),
+    };
+    let ids = |values: &Option<ValueFilter<i32>>| -> Vec<GroupValue> {
+        values.as_ref().map_or_else(Vec::new, |v| v.include.iter().map(|&id| GroupValue::Number(id as i64)).collect())
+    };
+    let values = match dimension {
+        GroupDimension::Nationality => ids(&f.country),
+        GroupDimension::Club => ids(&f.club),
+        GroupDimension::FavouriteTeam => ids(&f.favourite_club),
+        GroupDimension::Position => f.position.as_ref().map_or_else(Vec::new, |positions| {
+            positions.include.iter().map(|p| GroupValue::Text(p.clone())).collect()
+        }),
+        GroupDimension::BirthYear => match (f.birth_year_min, f.birth_year_max) {
+            (Some(min), Some(max)) => {
+                // Check the span before expanding it, the bounds come straight from the spec
+                if (max as i64 - min as i64) >= MAX_EXPECTED_GROUPS as i64 {
+                    return Err(format!("Birth years {}-{} span more than {} groups", min, max, MAX_EXPECTED_GROUPS));
+                }
+                (min..=max).map(|year| GroupValue::Number(year as i64)).collect()
+            }
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    Ok(values)
+}
+
+/// Every combination of the listed and observed values of each dimension.
+/// Fails when the combinations would exceed `MAX_EXPECTED_GROUPS`.
+fn expected_groups(
+    target: &BalanceTarget,
+    filters: Option<&PlayerFilters>,
+    observed: &HashMap<Vec<GroupValue>, Vec<&Player>>,
+) -> Result<Vec<Vec<GroupValue>>, String> {
+    let mut dimension_values = Vec::with_capacity(target.group_by.len());
+    let mut total: usize = 1;
+    for (i, &dimension) in target.group_by.iter().enumerate() {
+        let mut values = listed_values(dimension, filters)?;
+        for key in observed.keys() {
+            if !values.contains(&key[i]) {
+                values.push(key[i].clone());
+            }
+        }
+        values.sort_by(|a, b| dimension.compare(a, b, DEFAULT_BAND_WIDTH));
+        total = total.saturating_mul(values.len());
+        dimension_values.push(values);
+    }
+    if total > MAX_EXPECTED_GROUPS {
+        return Err(format!(
+            "Target '{}' expands to {} groups, the limit is {}",
+            target.name, total, MAX_EXPECTED_GROUPS
+        ));
+    }
+
+    let mut groups: Vec<Vec<GroupValue>> = vec![Vec::new()];
+    for values in &dimension_values {
+        groups = groups
+            .into_iter()
+            .flat_map(|group| {
+                values.iter().map(move |value| {
+                    let mut group = group.clone();
+                    group.push(value.clone());
+                    group
+                })
+            })
+            .collect();
+    }
+    Ok(groups)
+}
+
+fn band_label(band: &BandTarget) -> String {
+    let field = band.field.name();
+    match (band.min, band.max) {
+        (Some(min), Some(max)) => format!("{} {}-{}", field, min, max),
+        (Some(min), None) => format!("{} {}+", field, min),
+        (None, Some(max)) => format!("{} up to {}", field, max),
+        (None, None) => field.to_string(),
+    }
+}
+
+/// Running totals of the audit
+#[derive(Default)]
+struct Audit {
+    checks: usize,
+    passed: usize,
+    score: f64,
+    shortfalls: Vec<BalanceIssue>,
+    surpluses: Vec<BalanceIssue>,
+}
+
+impl Audit {
+    /// Check one count against its bounds. A failed check scores
+    /// 1 - (distance to the bound / bound), never below 0.
+    fn check(&mut self, issue: BalanceIssue) {
+        if issue.expected_min.is_none() && issue.expected_max.is_none() {
+            return;
+        }
+        self.checks += 1;
+        if let Some(min) = issue.expected_min.filter(|&min| issue.actual < min) {
+            self.score += 1.0 - ((min - issue.actual) as f64 / min as f64).min(1.0);
+            self.shortfalls.push(issue);
+        } else if let Some(max) = issue.expected_max.filter(|&max| issue.actual > max) {
+            self.score += 1.0 - ((issue.actual - max) as f64 / max.max(1) as f64).min(1.0);
+            self.surpluses.push(issue);
+        } else {
+            self.passed += 1;
+            self.score += 1.0;
+        }
+    }
+}
+
+/// Check the loaded players against the targets of a spec file and report
+/// every group that falls short of or exceeds its expected counts
+#[tauri::command]
+pub fn audit_balance(spec_path: String) -> Result<BalanceAudit, String> {
+    let spec = read_spec(&spec_path)?;
+    println!("[AUDIT] Checking {} balance targets from {}", spec.targets.len(), spec_path);
+
+    // Resolve presets before taking the players lock
+    let resolved = spec.targets
+        .iter()
+        .map(|target| resolve_filters(target.filters.clone()))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let players = get_players().lock().unwrap();
+    let mut audit = Audit::default();
+    for (target, filters) in spec.targets.iter().zip(resolved) {
+        let ids = filtered_ids(&players, filters.as_ref())?;
+
+        let mut observed: HashMap<Vec<GroupValue>, Vec<&Player>> = HashMap::new();
+        for id in &ids {
+            if let Some(player) = players.get(id) {
+                let key = target.group_by.iter().map(|d| d.value(player, DEFAULT_BAND_WIDTH)).collect();
+                observed.entry(key).or_default().push(player);
+            }
+        }
+
+        for group in expected_groups(target, filters.as_ref(), &observed)? {
+            let members = observed.get(&group).map(Vec::as_slice).unwrap_or_default();
+            let keys: Vec<_> = target.group_by
+                .iter()
+                .zip(&group)
+                .map(|(dimension, value)| dimension.key(value, DEFAULT_BAND_WIDTH))
+                .collect();
+            audit.check(BalanceIssue {
+                target: target.name.clone(),
+                group: keys.clone(),
+                check: "count".to_string(),
+                actual: members.len(),
+                expected_min: target.min_count,
+                expected_max: target.max_count,
+            });
+            for band in &target.bands {
+                let actual = members
+                    .iter()
+                    .filter_map(|player| band.field.value(player))
+                    .filter(|&value| band.min.is_none_or(|min| value >= min) && band.max.is_none_or(|max| value <= max))
+                    .count();
+                audit.check(BalanceIssue {
+                    target: target.name.clone(),
+                    group: keys.clone(),
+                    check: band_label(band),
+                    actual,
+                    expected_min: band.min_count,
+                    expected_max: band.max_count,
+                });
+            }
+        }
+    }
+
+    let health_score = if audit.checks == 0 { 100.0 } else { 100.0 * audit.score / audit.checks as f64 };
+    println!(
+        "[AUDIT] {} of {} checks passed, health score {:.1}",
+        audit.passed, audit.checks, health_score
+    );
+    Ok(BalanceAudit {
+        checks: audit.checks,
+        passed: audit.passed,
+        shortfalls: audit.shortfalls,
+        surpluses: audit.surpluses,
+        health_score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(group_by: Vec<GroupDimension>) -> BalanceTarget {
+        BalanceTarget {
+            name: "test".to_string(),
+            filters: None,
+            group_by,
+            min_count: Some(1),
+            max_count: None,
+            bands: Vec::new(),
+        }
+    }
+
+    fn birth_years(min: i32, max: i32) -> PlayerFilters {
+        PlayerFilters {
+            birth_year_min: Some(min),
+            birth_year_max: Some(max),
+            ..PlayerFilters::default()
+        }
+    }
+
+    #[test]
+    fn listed_birth_years_become_groups() {
+        let filters = birth_years(2005, 2007);
+        let groups = expected_groups(&target(vec![GroupDimension::BirthYear]), Some(&filters), &HashMap::new()).unwrap();
+        let years: Vec<_> = groups.iter().map(|group| group[0].clone()).collect();
+        assert_eq!(years, vec![GroupValue::Number(2005), GroupValue::Number(2006), GroupValue::Number(2007)]);
+    }
+
+    #[test]
+    fn wide_birth_year_range_is_rejected() {
+        let filters = birth_years(i32::MIN, i32::MAX);
+        assert!(expected_groups(&target(vec![GroupDimension::BirthYear]), Some(&filters), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn combinations_over_the_limit_are_rejected() {
+        // Each dimension alone is within the limit, their product is not
+        let filters = birth_years(0, 199);
+        let by = target(vec![GroupDimension::BirthYear, GroupDimension::BirthYear]);
+        assert!(expected_groups(&by, Some(&filters), &HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::utils::remove_accents;
+
+/// Alphabet used to order text sort keys.
+/// `Root` orders accented letters together with their base letter; the
+/// national locales place their extra letters (Polish "ł", Czech "ch",
+/// Swedish "å"...) where that language's alphabet puts them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollationLocale {
+    #[default]
+    Root,
+    Polish,
+    Czech,
+    Slovak,
+    German,
+    Swedish,
+    Danish,
+    Spanish,
+    Turkish,
+    Hungarian,
+    Croatian,
+    Romanian,
+}
+
+impl CollationLocale {
+    /// Parse a language tag such as "pl", "cs-CZ" or "sv_FI"
+    pub fn parse(tag: &str) -> Result<CollationLocale, String> {
+        let language = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let locale = match language.as_str() {
+            "" | "root" | "und" | "en" => CollationLocale::Root,
+            "pl" => CollationLocale::Polish,
+            "cs" => CollationLocale::Czech,
+            "sk" => CollationLocale::Slovak,
+            "de" => CollationLocale::German,
+            "sv" | "fi" => CollationLocale::Swedish,
+            "da" | "no" | "nb" | "nn" => CollationLocale::Danish,
+            "es" => CollationLocale::Spanish,
+            "tr" | "az" => CollationLocale::Turkish,
+            "hu" => CollationLocale::Hungarian,
+            "hr" | "bs" | "sr" => CollationLocale::Croatian,
+            "ro" => CollationLocale::Romanian,
+            _ => return Err(format!("Unsupported sort locale: {}", tag)),
+        };
+        Ok(locale)
+    }
+
+    /// Letters (or letter groups) this locale sorts as separate letters.
+    /// Returns how many characters were consumed, the base letter the new letter
+    /// follows and its position after that letter.
+    fn tailoring(self, chars: &[char]) -> Option<(usize, char, u32)> {
+        use CollationLocale::*;
+        let tailored = match (self, chars) {
+            (Polish, ['ą', ..]) => (1, 'a', 1),
+            (Polish, ['ć', ..]) => (1, 'c', 1),
+            (Polish, ['ę', ..]) => (1, 'e', 1),
+            (Polish, ['ł', ..]) => (1, 'l', 1),
+            (Polish, ['ń', ..]) => (1, 'n', 1),
+            (Polish, ['ó', ..]) => (1, 'o', 1),
+            (Polish, ['ś', ..]) => (1, 's', 1),
+            (Polish, ['ź', ..]) => (1, 'z', 1),
+            (Polish, ['ż', ..]) => (1, 'z', 2),
+
+            (Czech | Slovak, ['c', 'h', ..]) => (2, 'h', 1),
+            (Czech | Slovak, ['č', ..]) => (1, 'c', 1),
+            (Czech | Slovak, ['š', ..]) => (1, 's', 1),
+            (Czech | Slovak, ['ž', ..]) => (1, 'z', 1),
+            (Czech, ['ř', ..]) => (1, 'r', 1),
+            (Slovak, ['ä', ..]) => (1, 'a', 1),
+            (Slovak, ['ô', ..]) => (1, 'o', 1),
+
+            (Swedish, ['å', ..]) => (1, 'z', 1),
+            (Swedish, ['ä' | 'æ', ..]) => (1, 'z', 2),
+            (Swedish, ['ö' | 'ø', ..]) => (1, 'z', 3),
+
+            (Danish, ['æ' | 'ä', ..]) => (1, 'z', 1),
+            (Danish, ['ø' | 'ö', ..]) => (1, 'z', 2),
+            (Danish, ['å', ..]) => (1, 'z', 3),
+
+            (Spanish, ['ñ', ..]) => (1, 'n', 1),
+
+            (Turkish, ['ç', ..]) => (1, 'c', 1),
+            (Turkish, ['ğ', ..]) => (1, 'g', 1),
+            (Turkish, ['ı', ..]) => (1, 'h', 1),
+            (Turkish, ['ö', ..]) => (1, 'o', 1),
+            (Turkish, ['ş', ..]) => (1, 's', 1),
+            (Turkish, ['ü', ..]) => (1, 'u', 1),
+
+            (Hungarian, ['d', 'z', 's', ..]) => (3, 'd', 2),
+            (Hungarian, ['c', 's', ..]) => (2, 'c', 1),
+            (Hungarian, ['d', 'z', ..]) => (2, 'd', 1),
+            (Hungarian, ['g', 'y', ..]) => (2, 'g', 1),
+            (Hungarian, ['l', 'y', ..]) => (2, 'l', 1),
+            (Hungarian, ['n', 'y', ..]) => (2, 'n', 1),
+            (Hungarian, ['s', 'z', ..]) => (2, 's', 1),
+            (Hungarian, ['t', 'y', ..]) => (2, 't', 1),
+            (Hungarian, ['z', 's', ..]) => (2, 'z', 1),
+            (Hungarian, ['ö' | 'ő', ..]) => (1, 'o', 1),
+            (Hungarian, ['ü' | 'ű', ..]) => (1, 'u', 1),
+
+            (Croatian, ['d', 'ž', ..]) => (2, 'd', 1),
+            (Croatian, ['l', 'j', ..]) => (2, 'l', 1),
+            (Croatian, ['n', 'j', ..]) => (2, 'n', 1),
+            (Croatian, ['č', ..]) => (1, 'c', 1),
+            (Croatian, ['ć', ..]) => (1, 'c', 2),
+            (Croatian, ['đ', ..]) => (1, 'd', 2),
+            (Croatian, ['š', ..]) => (1, 's', 1),
+            (Croatian, ['ž', ..]) => (1, 'z', 1),
+
+            (Romanian, ['ă', ..]) => (1, 'a', 1),
+            (Romanian, ['â', ..]) => (1, 'a', 2),
+            (Romanian, ['î', ..]) => (1, 'i', 1),
+            (Romanian, ['ș' | 'ş', ..]) => (1, 's', 1),
+            (Romanian, ['ț' | 'ţ', ..]) => (1, 't', 1),
+
+            _ => return None,
+        };
+        Some(tailored)
+    }
+}
+
+/// Precomputed collation key; comparing two keys orders the original strings.
+/// Holds three levels like the Unicode Collation Algorithm: base letters,
+/// then accents, then case, so "lopez" < "López" < "Lopez2" and accents only
+/// break ties between otherwise equal names.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CollationKey(Vec<u32>);
+
+/// Level separator, lower than any weight
+const LEVEL_SEPARATOR: u32 = 0;
+const SPACE_WEIGHT: u32 = 1;
+const PLAIN: u32 = 1;
+const LOWERCASE: u32 = 1;
+const UPPERCASE: u32 = 2;
+
+/// Primary weight of a base letter; tailored letters slot in right after it
+fn primary_weight(base: char, rank: u32) -> u32 {
+    (base as u32) * 4 + rank
+}
+
+/// Collation key of `s` in the given locale. Computed once per value, then compared cheaply.
+pub fn collation_key(s: &str, locale: CollationLocale) -> CollationKey {
+    let original: Vec<char> = s.nfc().collect();
+    let lower: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let mut primary = Vec::with_capacity(original.len());
+    let mut secondary = Vec::with_capacity(original.len());
+    let mut tertiary = Vec::with_capacity(original.len());
+
+    let mut i = 0;
+    while i < original.len() {
+        let case = if original[i].is_uppercase() { UPPERCASE } else { LOWERCASE };
+
+        if let Some((consumed, base, rank)) = locale.tailoring(&lower[i..]) {
+            primary.push(primary_weight(base, rank));
+            secondary.push(PLAIN + lower[i] as u32);
+            tertiary.push(case);
+            i += consumed;
+            continue;
+        }
+
+        let c = lower[i];
+        if c.is_whitespace() {
+            primary.push(SPACE_WEIGHT);
+            secondary.push(PLAIN);
+            tertiary.push(LOWERCASE);
+        } else {
+            // Base letters come from stripping the accents; an accented letter
+            // differs from its base letter only at the second level
+            let mut buf = [0; 4];
+            let letter: &str = c.encode_utf8(&mut buf);
+            let bases = remove_accents(letter);
+            let accent = if bases == letter { PLAIN } else { PLAIN + c as u32 };
+            // Punctuation (hyphens, apostrophes, dots) is ignored
+            for base in bases.chars().filter(|b| b.is_alphanumeric()) {
+                primary.push(primary_weight(base, 0));
+                secondary.push(accent);
+                tertiary.push(case);
+            }
+        }
+        i += 1;
+    }
+
+    let mut key = primary;
+    key.push(LEVEL_SEPARATOR);
+    key.extend(secondary);
+    key.push(LEVEL_SEPARATOR);
+    key.extend(tertiary);
+    CollationKey(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str, locale: CollationLocale) -> CollationKey {
+        collation_key(s, locale)
+    }
+
+    #[test]
+    fn parses_language_tags() {
+        assert_eq!(CollationLocale::parse("cs-CZ"), Ok(CollationLocale::Czech));
+        assert_eq!(CollationLocale::parse("sv_FI"), Ok(CollationLocale::Swedish));
+        assert_eq!(CollationLocale::parse(""), Ok(CollationLocale::Root));
+        assert!(CollationLocale::parse("xx").is_err());
+    }
+
+    #[test]
+    fn accents_and_case_only_break_ties() {
+        let root = CollationLocale::Root;
+        assert!(key("lopez", root) < key("López", root));
+        assert!(key("López", root) < key("Lopez2", root));
+        assert!(key("Émile", root) < key("Eric", root));
+        assert_ne!(key("Müller", root), key("Muller", root));
+        assert!(key("Müller", root) < key("Mullet", root));
+    }
+
+    #[test]
+    fn punctuation_is_ignored() {
+        let root = CollationLocale::Root;
+        assert!(key("De-Jong", root) < key("Dek", root));
+        assert!(key("O'Brien", root) > key("Obama", root));
+    }
+
+    #[test]
+    fn locales_place_their_own_letters() {
+        assert!(key("Łukasik", CollationLocale::Root) < key("Lwowski", CollationLocale::Root));
+        assert!(key("Łukasik", CollationLocale::Polish) > key("Lwowski", CollationLocale::Polish));
+        assert!(key("Džeko", CollationLocale::Croatian) > key("Dzeko", CollationLocale::Croatian));
+        assert!(key("Džeko", CollationLocale::Croatian) > key("Dziedzic", CollationLocale::Croatian));
+    }
+}
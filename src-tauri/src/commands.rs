@@ -1,8 +1,65 @@
-use crate::get_invalid_rows;
-use crate::model::InvalidRow;
+use crate::{age_reference, bump_players_version, get_invalid_rows, store_age_reference};
+use crate::model::{Date, InvalidRow};
 
 #[tauri::command]
 pub fn get_invalid_rows_list() -> Vec<InvalidRow> {
     let invalid_rows = get_invalid_rows().lock().unwrap();
     invalid_rows.clone()
 }
+
+/// Date in-game ages are computed at, as DD/MM/YYYY
+#[tauri::command]
+pub fn get_age_reference() -> Option<String> {
+    age_reference().map(|date| date.to_string())
+}
+
+/// Change the date in-game ages are computed at, e.g. to see ages in a later
+/// season. `date` is DD/MM/YYYY (or another format `Date::parse` accepts); `season` picks the start of the season
+/// beginning in that year. Loading players resets it to the game start date, or
+/// leaves it unset when no game year is configured.
+#[tauri::command]
+pub fn set_age_reference(date: Option<String>, season: Option<i32>) -> Result<String, String> {
+    let reference = parse_age_reference(date, season)?;
+    store_age_reference(Some(reference));
+    // Cached queries may filter or sort by age
+    bump_players_version();
+    println!("[AGE] Reference date set to {}", reference);
+    Ok(reference.to_string())
+}
+
+/// Reference date `set_age_reference` would store, without storing it
+fn parse_age_reference(date: Option<String>, season: Option<i32>) -> Result<Date, String> {
+    match (date, season) {
+        (Some(date), _) => Date::parse(&date),
+        // Keep to the four-digit years dates are written with
+        (None, Some(season)) if (1000..=9999).contains(&season) => Ok(Date::game_start(season + 1)),
+        (None, Some(season)) => Err(format!("Season {} is outside 1000-9999", season)),
+        (None, None) => Err("Either a date or a season is required".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn season_out_of_range_is_rejected() {
+        assert!(parse_age_reference(None, Some(-5)).is_err());
+        assert!(parse_age_reference(None, Some(999)).is_err());
+        assert!(parse_age_reference(None, Some(10_000)).is_err());
+        assert!(parse_age_reference(None, None).is_err());
+    }
+
+    #[test]
+    fn season_picks_its_start_date() {
+        assert_eq!(parse_age_reference(None, Some(1000)), Ok(Date::new(1, 7, 1000).unwrap()));
+        assert_eq!(parse_age_reference(None, Some(2030)), Ok(Date::new(1, 7, 2030).unwrap()));
+    }
+
+    #[test]
+    fn date_wins_over_season() {
+        let reference = parse_age_reference(Some("15/01/2031".to_string()), Some(2030));
+        assert_eq!(reference, Ok(Date::new(15, 1, 2031).unwrap()));
+        assert!(parse_age_reference(Some("31/02/2031".to_string()), None).is_err());
+    }
+}
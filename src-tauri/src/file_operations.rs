@@ -2,9 +2,28 @@ use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::io::{BufRead, BufReader, Write};
 
-use crate::model::{Player, RecordType, PlayerFilters, InvalidRow};
-use crate::{get_players, get_invalid_rows};
-use crate::utils::{get_birth_year, matches_search_query, is_birth_date_in_range};
+use crate::model::{Date, Player, RecordType, PlayerFilters, InvalidRow};
+use crate::{get_players, get_player_index, get_invalid_rows, bump_players_version, store_age_reference};
+use crate::player_filters::filtered_ids;
+use crate::utils::normalize_name;
+use crate::filter_presets::resolve_filters;
+
+/// Copy of the player with names normalized, so rows that differ only in
+/// accents, case or Unicode encoding are treated as duplicates
+fn dedupe_key(player: &Player) -> Player {
+    let mut key = player.clone();
+    key.first_name = normalize_name(&player.first_name);
+    key.last_name = normalize_name(&player.last_name);
+    key.common_name = player.common_name.as_deref().map(normalize_name);
+    key.birth_city = player.birth_city.as_deref().map(normalize_name);
+    key
+}
+
+/// Date in-game ages are computed at after a load. The frontend sends game
+/// year 0 when no edition is configured; ages then stay unset.
+fn loaded_age_reference(game_year: i32) -> Option<Date> {
+    (game_year > 0).then(|| Date::game_start(game_year))
+}
 
 #[tauri::command]
 pub fn load_players_from_file(
@@ -66,16 +85,18 @@ pub fn load_players_from_file(
                 _ => continue,
             };
 
-            let mut birth_date = fields[4].to_string();
-            if convert_birthdates {
-                let parts: Vec<&str> = birth_date.split('/').collect();
-                if parts.len() == 3 {
-                    if let Ok(year) = parts[2].parse::<i32>() {
-                        let new_year = year + year_offset;
-                        birth_date = format!("{}/{}/{}", parts[0], parts[1], new_year);
-                    }
+            // Rows whose birth date isn't a real date are reported like malformed rows
+            let birth_date = match Date::parse(fields[4]) {
+                Ok(date) => date.shift_years(year_offset),
+                Err(_) => {
+                    get_invalid_rows().lock().unwrap().push(InvalidRow {
+                        row_number: line_idx + 1,
+                        content: line.to_string(),
+                        file_path: path.clone(),
+                    });
+                    continue;
                 }
-            }
+            };
 
             let player = Player {
                 record_type,
@@ -107,11 +128,10 @@ pub fn load_players_from_file(
                 club_id: fields.get(18).and_then(|s| s.parse().ok()),
             };
 
-            if unique_players.contains(&player) {
+            if !unique_players.insert(dedupe_key(&player)) {
                 continue;
             }
 
-            unique_players.insert(player.clone());
             loaded_players.insert(global_idx, player);
             global_idx += 1;
         }
@@ -119,6 +139,9 @@ pub fn load_players_from_file(
 
     let mut players = get_players().lock().unwrap();
     *players = loaded_players;
+    get_player_index().lock().unwrap().rebuild(&players);
+    store_age_reference(loaded_age_reference(game_year));
+    bump_players_version();
     println!("Loaded {} players...", players.len());
 
     Ok(())
@@ -145,12 +168,13 @@ pub fn append_players_from_file(
     let reader = BufReader::new(file);
     
     let mut players = get_players().lock().unwrap();
+    let mut index = get_player_index().lock().unwrap();
     
     // Find next available index
     let mut next_idx = players.keys().max().map(|k| k + 1).unwrap_or(0);
     
     // Build set of existing players for deduplication
-    let mut existing_players: HashSet<Player> = players.values().cloned().collect();
+    let mut existing_players: HashSet<Player> = players.values().map(dedupe_key).collect();
     
     let mut appended_count = 0;
 
@@ -183,16 +207,17 @@ pub fn append_players_from_file(
         };
 
         // Convert birth date
-        let mut birth_date = fields[4].to_string();
-        if year_offset != 0 {
-            let parts: Vec<&str> = birth_date.split('/').collect();
-            if parts.len() == 3 {
-                if let Ok(year) = parts[2].parse::<i32>() {
-                    let new_year = year + year_offset;
-                    birth_date = format!("{}/{}/{}", parts[0], parts[1], new_year);
-                }
+        let birth_date = match Date::parse(fields[4]) {
+            Ok(date) => date.shift_years(year_offset),
+            Err(_) => {
+                get_invalid_rows().lock().unwrap().push(InvalidRow {
+                    row_number: line_idx + 1,
+                    content: line.to_string(),
+                    file_path: path.clone(),
+                });
+                continue;
             }
-        }
+        };
 
         let player = Player {
             record_type,
@@ -225,16 +250,19 @@ pub fn append_players_from_file(
         };
 
         // Skip duplicates
-        if existing_players.contains(&player) {
+        if !existing_players.insert(dedupe_key(&player)) {
             continue;
         }
 
-        existing_players.insert(player.clone());
+        index.insert(next_idx, &player);
         players.insert(next_idx, player);
         next_idx += 1;
         appended_count += 1;
     }
 
+    if appended_count > 0 {
+        bump_players_version();
+    }
     println!("[APPEND] Added {} new players, total now: {}", appended_count, players.len());
 
     Ok(appended_count)
@@ -242,8 +270,11 @@ pub fn append_players_from_file(
 
 #[tauri::command]
 pub fn save_players_to_file(path: String, filters: Option<PlayerFilters>) -> Result<(), String> {
+    let filters = resolve_filters(filters)?;
     let players = get_players().lock().map_err(|e| e.to_string())?;
-    let mut file = fs::File::create(path).unwrap();
+    // Resolve the selection before touching the file so a bad filter can't truncate it
+    let ids = filtered_ids(&players, filters.as_ref())?;
+    let mut file = fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
     let mut saved_count = 0;
 
     // Debug: print filters
@@ -262,117 +293,8 @@ pub fn save_players_to_file(path: String, filters: Option<PlayerFilters>) -> Res
         println!("[SAVE] No filters - saving all players");
     }
 
-    for (id, player) in players.iter() {
-        // Apply filters if present
-        if let Some(ref f) = filters {
-            // Player IDs filter (for selected players) - check this first for performance
-            if let Some(ref ids) = f.player_ids {
-                if !ids.contains(id) {
-                    continue;
-                }
-            }
-
-            // Name filter
-            if let Some(ref query) = f.name_query {
-                if !matches_search_query(player, query) {
-                    continue;
-                }
-            }
-            
-            // Country filter
-            if let Some(cid) = f.country {
-                if player.nationality_id != cid {
-                    continue;
-                }
-            }
-            
-            // Club filter
-            if let Some(cid) = f.club {
-                if player.club_id != Some(cid) {
-                    continue;
-                }
-            }
-            
-            // CA range filter
-            if let Some(min_ca) = f.min_ca {
-                if let Some(ca) = player.ca {
-                    if ca < min_ca {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-            }
-            if let Some(max_ca) = f.max_ca {
-                if let Some(ca) = player.ca {
-                    if ca > max_ca {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-            }
-            
-            // PA range filter
-            if let Some(min_pa) = f.min_pa {
-                if let Some(pa) = player.pa {
-                    if pa < min_pa {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-            }
-            if let Some(max_pa) = f.max_pa {
-                if let Some(pa) = player.pa {
-                    if pa > max_pa {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-            }
-            
-            // Preferred foot filter
-            if let Some(foot) = f.preferred_foot {
-                if player.preferred_foot != Some(foot) {
-                    continue;
-                }
-            }
-            
-            // Favourite number filter
-            if let Some(fav_num) = f.favourite_number {
-                if player.favourite_number != Some(fav_num) {
-                    continue;
-                }
-            }
-            
-            // Birth year filter
-            if let Some(birth_year) = f.birth_year_min {
-                if let Some(player_birth_year) = get_birth_year(&player.birth_date) {
-                    if player_birth_year != birth_year {
-                        continue;
-                    }
-                } else {
-                    continue;
-                }
-            }
-            
-            // Birth date range filter (day/month within a year)
-            if f.birth_day_from.is_some() || f.birth_month_from.is_some() || 
-               f.birth_day_to.is_some() || f.birth_month_to.is_some() {
-                if !is_birth_date_in_range(
-                    &player.birth_date,
-                    f.birth_day_from,
-                    f.birth_month_from,
-                    f.birth_day_to,
-                    f.birth_month_to,
-                ) {
-                    continue;
-                }
-            }
-        }
-
+    for id in ids {
+        let player = &players[&id];
         let line = format!(
             "\"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\"\n",
             player.record_type,
@@ -396,7 +318,7 @@ pub fn save_players_to_file(path: String, filters: Option<PlayerFilters>) -> Res
             player.club_id.map_or(String::new(), |v| v.to_string()),
         );
 
-        file.write_all(line.as_bytes()).unwrap();
+        file.write_all(line.as_bytes()).map_err(|e| format!("Failed to write {}: {}", path, e))?;
         saved_count += 1;
     }
 
@@ -404,3 +326,21 @@ pub fn save_players_to_file(path: String, filters: Option<PlayerFilters>) -> Res
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn age_reference_needs_a_game_year() {
+        assert_eq!(loaded_age_reference(0), None);
+        assert_eq!(loaded_age_reference(-3), None);
+        assert_eq!(loaded_age_reference(2024), Date::new(1, 7, 2023));
+    }
+
+    #[test]
+    fn unwritable_save_path_is_an_error() {
+        let path = std::env::temp_dir().join("fmforge_missing_dir").join("players.txt");
+        assert!(save_players_to_file(path.to_string_lossy().into(), None).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::OnceCell;
+
+use crate::get_filter_presets;
+use crate::model::{FilterPreset, PlayerFilters};
+
+const PRESETS_FILE_NAME: &str = "filter_presets.json";
+
+static PRESETS_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+/// Load stored presets from the app config directory. Called once on startup.
+pub fn init_filter_presets(config_dir: PathBuf) {
+    let path = config_dir.join(PRESETS_FILE_NAME);
+    if path.exists() {
+        match read_presets_file(&path) {
+            Ok(loaded) => {
+                println!("[PRESETS] Loaded {} filter presets from {}", loaded.len(), path.display());
+                *get_filter_presets().lock().unwrap() = loaded;
+            }
+            Err(e) => println!("[PRESETS] Ignoring unreadable presets file: {}", e),
+        }
+    }
+    let _ = PRESETS_PATH.set(path);
+}
+
+fn read_presets_file(path: &Path) -> Result<BTreeMap<String, PlayerFilters>, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid presets file {}: {}", path.display(), e))
+}
+
+fn write_presets_file(path: &Path, presets: &BTreeMap<String, PlayerFilters>) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    // Drop unset filters so shared files only list what each preset actually uses
+    let mut value = serde_json::to_value(presets).map_err(|e| e.to_string())?;
+    if let Some(presets) = value.as_object_mut() {
+        for filters in presets.values_mut() {
+            if let Some(fields) = filters.as_object_mut() {
+                fields.retain(|_, v| !v.is_null());
+            }
+        }
+    }
+    let json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn persist(presets: &BTreeMap<String, PlayerFilters>) -> Result<(), String> {
+    let path = PRESETS_PATH.get().ok_or("Filter presets are not initialised")?;
+    write_presets_file(path, presets)
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Replace a `preset` reference with the stored filters.
+/// Fields set on the request take precedence over the preset's own values.
+pub fn resolve_filters(filters: Option<PlayerFilters>) -> Result<Option<PlayerFilters>, String> {
+    let filters = match filters {
+        Some(f) => f,
+        None => return Ok(None),
+    };
+    let name = match filters.preset {
+        Some(ref name) => name.clone(),
+        None => return Ok(Some(filters)),
+    };
+
+    let preset = get_filter_presets()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("Unknown filter preset: {}", name))?;
+
+    let mut merged = serde_json::to_value(preset).map_err(|e| e.to_string())?;
+    let overrides = serde_json::to_value(filters).map_err(|e| e.to_string())?;
+    if let (Some(base), Some(overrides)) = (merged.as_object_mut(), overrides.as_object()) {
+        for (key, value) in overrides {
+            if !value.is_null() && key != "preset" {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    let mut resolved: PlayerFilters = serde_json::from_value(merged).map_err(|e| e.to_string())?;
+    resolved.preset = None;
+    Ok(Some(resolved))
+}
+
+#[tauri::command]
+pub fn list_filter_presets() -> Result<Vec<FilterPreset>, String> {
+    let presets = get_filter_presets().lock().map_err(|e| e.to_string())?;
+    Ok(presets
+        .iter()
+        .map(|(name, filters)| FilterPreset {
+            name: name.clone(),
+            filters: filters.clone(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn save_filter_preset(name: String, filters: PlayerFilters) -> Result<(), String> {
+    let name = validate_name(&name)?;
+    // Store presets flattened so they never depend on another preset
+    let filters = resolve_filters(Some(filters))?.unwrap_or_default();
+
+    let mut presets = get_filter_presets().lock().map_err(|e| e.to_string())?;
+    presets.insert(name.clone(), filters);
+    persist(&presets)?;
+    println!("[PRESETS] Saved preset: {}", name);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn rename_filter_preset(old_name: String, new_name: String) -> Result<(), String> {
+    let new_name = validate_name(&new_name)?;
+    let mut presets = get_filter_presets().lock().map_err(|e| e.to_string())?;
+    if old_name == new_name {
+        return Ok(());
+    }
+    if presets.contains_key(&new_name) {
+        return Err(format!("A preset named {} already exists", new_name));
+    }
+    let filters = presets
+        .remove(&old_name)
+        .ok_or_else(|| format!("Unknown filter preset: {}", old_name))?;
+    presets.insert(new_name, filters);
+    persist(&presets)
+}
+
+#[tauri::command]
+pub fn delete_filter_preset(name: String) -> Result<(), String> {
+    let mut presets = get_filter_presets().lock().map_err(|e| e.to_string())?;
+    if presets.remove(&name).is_none() {
+        return Err(format!("Unknown filter preset: {}", name));
+    }
+    persist(&presets)
+}
+
+/// Write the selected presets (all when `names` is empty) to a shareable JSON file
+#[tauri::command]
+pub fn export_filter_presets(path: String, names: Option<Vec<String>>) -> Result<usize, String> {
+    let presets = get_filter_presets().lock().map_err(|e| e.to_string())?;
+    let exported: BTreeMap<String, PlayerFilters> = match names {
+        Some(ref names) if !names.is_empty() => {
+            let mut selected = BTreeMap::new();
+            for name in names {
+                let filters = presets
+                    .get(name)
+                    .ok_or_else(|| format!("Unknown filter preset: {}", name))?;
+                selected.insert(name.clone(), filters.clone());
+            }
+            selected
+        }
+        _ => presets.clone(),
+    };
+    write_presets_file(Path::new(&path), &exported)?;
+    println!("[PRESETS] Exported {} presets to {}", exported.len(), path);
+    Ok(exported.len())
+}
+
+/// Merge presets from a file written by `export_filter_presets`.
+/// Existing presets with the same name are kept unless `overwrite` is set.
+#[tauri::command]
+pub fn import_filter_presets(path: String, overwrite: bool) -> Result<usize, String> {
+    let imported = read_presets_file(Path::new(&path))?;
+    let mut presets = get_filter_presets().lock().map_err(|e| e.to_string())?;
+    let mut imported_count = 0;
+    for (name, mut filters) in imported {
+        if !overwrite && presets.contains_key(&name) {
+            continue;
+        }
+        filters.preset = None;
+        presets.insert(name, filters);
+        imported_count += 1;
+    }
+    persist(&presets)?;
+    println!("[PRESETS] Imported {} presets from {}", imported_count, path);
+    Ok(imported_count)
+}
@@ -1,41 +1,120 @@
 use once_cell::sync::Lazy;
 use std::collections::BTreeMap;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 mod model;
 mod file_operations;
 mod player_queries;
+mod player_filters;
+mod player_sorting;
+mod player_index;
+mod query_cache;
+mod filter_presets;
 mod player_management;
 mod player_statistics;
+mod player_dimensions;
+mod player_seasonality;
+mod balance_audit;
+mod talent_pipeline;
+mod regen_eligibility;
+mod reports;
+mod svg_charts;
 mod utils;
+mod transliteration;
+mod collation;
+mod search_query;
 mod commands;
+#[cfg(test)]
+mod test_support;
 
-use crate::model::{Player, InvalidRow};
+use crate::model::{Date, Player, InvalidRow, PlayerFilters};
+use crate::player_index::PlayerIndex;
+use crate::query_cache::QueryCache;
 
 static PLAYERS: Lazy<Mutex<BTreeMap<usize, Player>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+static PLAYER_INDEX: Lazy<Mutex<PlayerIndex>> = Lazy::new(|| Mutex::new(PlayerIndex::default()));
+static PLAYERS_VERSION: AtomicU64 = AtomicU64::new(0);
+static QUERY_CACHE: Lazy<Mutex<QueryCache>> = Lazy::new(|| Mutex::new(QueryCache::default()));
 static INVALID_ROWS: Lazy<Mutex<Vec<InvalidRow>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Date in-game ages are computed at; `None` while unset
+static AGE_REFERENCE: Mutex<Option<Date>> = Mutex::new(None);
+static FILTER_PRESETS: Lazy<Mutex<BTreeMap<String, PlayerFilters>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
 
 pub fn get_players() -> &'static Mutex<BTreeMap<usize, Player>> {
     &PLAYERS
 }
 
+pub fn get_player_index() -> &'static Mutex<PlayerIndex> {
+    &PLAYER_INDEX
+}
+
+/// Version of `PLAYERS`, bumped on every change so cached query results can tell they are stale
+pub fn players_version() -> u64 {
+    PLAYERS_VERSION.load(Ordering::SeqCst)
+}
+
+pub fn bump_players_version() {
+    PLAYERS_VERSION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Reference date for in-game ages, set from the game year on load
+pub fn age_reference() -> Option<Date> {
+    *AGE_REFERENCE.lock().unwrap()
+}
+
+pub fn store_age_reference(date: Option<Date>) {
+    *AGE_REFERENCE.lock().unwrap() = date;
+}
+
+pub fn get_query_cache() -> &'static Mutex<QueryCache> {
+    &QUERY_CACHE
+}
+
 pub fn get_invalid_rows() -> &'static Mutex<Vec<InvalidRow>> {
     &INVALID_ROWS
 }
 
+pub fn get_filter_presets() -> &'static Mutex<BTreeMap<String, PlayerFilters>> {
+    &FILTER_PRESETS
+}
+
 use file_operations::{
     load_players_from_file,
     save_players_to_file,
     append_players_from_file
 };
-use player_queries::{get_players_page, get_players_chunk, get_filtered_player_ids};
-use player_statistics::{get_player_statistics, get_top_players};
+use player_queries::{
+    get_players_page,
+    get_players_chunk,
+    get_filtered_player_ids,
+    create_players_query,
+    get_query_page,
+    release_players_query
+};
+use player_statistics::{get_player_statistics, get_top_players, group_stats, histogram, relationship_stats, get_leaderboard};
+use player_seasonality::birth_seasonality;
+use balance_audit::audit_balance;
+use talent_pipeline::project_talent_pipeline;
+use regen_eligibility::{check_regen_eligibility, fix_regen_eligibility};
+use reports::generate_report;
+use svg_charts::render_chart;
 use player_management::{
     update_players,
     add_new_player,
     remove_player
 };
-use commands::get_invalid_rows_list;
+use commands::{get_invalid_rows_list, get_age_reference, set_age_reference};
+use filter_presets::{
+    init_filter_presets,
+    list_filter_presets,
+    save_filter_preset,
+    rename_filter_preset,
+    delete_filter_preset,
+    export_filter_presets,
+    import_filter_presets
+};
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -43,6 +122,10 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            init_filter_presets(app.path().app_config_dir()?);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             load_players_from_file,
             append_players_from_file,
@@ -50,12 +133,34 @@ pub fn run() {
             get_players_page,
             get_player_statistics,
             get_top_players,
+            group_stats,
+            histogram,
+            relationship_stats,
+            get_leaderboard,
+            birth_seasonality,
+            audit_balance,
+            project_talent_pipeline,
+            check_regen_eligibility,
+            fix_regen_eligibility,
+            generate_report,
+            render_chart,
             update_players,
             add_new_player,
             remove_player,
             get_invalid_rows_list,
+            get_age_reference,
+            set_age_reference,
             save_players_to_file,
-            get_filtered_player_ids
+            get_filtered_player_ids,
+            create_players_query,
+            get_query_page,
+            release_players_query,
+            list_filter_presets,
+            save_filter_preset,
+            rename_filter_preset,
+            delete_filter_preset,
+            export_filter_presets,
+            import_filter_presets
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
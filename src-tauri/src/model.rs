@@ -1,5 +1,8 @@
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::str::FromStr;
+
+use crate::utils::{days_in_month, is_leap_year};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -14,7 +17,7 @@ pub struct Player {
     pub first_name: String,
     pub common_name: Option<String>,
     pub last_name: String,
-    pub birth_date: String,
+    pub birth_date: Date,
     pub nationality_id: i32,
     pub favourite_team_id: Option<i32>,
     pub ethnicity: i8,
@@ -35,29 +38,163 @@ pub struct Player {
 pub struct PlayerRecord {
     pub id: usize,
     pub player: Player,
+    /// Name search relevance (0-1), only set when the query had a name search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<f64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct PlayerFilters {
+    pub preset: Option<String>,
     pub player_ids: Option<Vec<usize>>,
-    pub country: Option<i32>,
-    pub club: Option<i32>,
+    pub country: Option<ValueFilter<i32>>,
+    pub club: Option<ValueFilter<i32>>,
     pub min_ca: Option<i32>,
     pub max_ca: Option<i32>,
     pub min_pa: Option<i32>,
     pub max_pa: Option<i32>,
-    pub preferred_foot: Option<i8>,
-    pub favourite_number: Option<i32>,
+    pub preferred_foot: Option<ValueFilter<i8>>,
+    pub favourite_number: Option<ValueFilter<i32>>,
     pub birth_year_min: Option<i32>,
     pub birth_year_max: Option<i32>,
+    /// In-game age range at the configured reference date
+    pub min_age: Option<i32>,
+    pub max_age: Option<i32>,
     pub birth_day_from: Option<u32>,
     pub birth_month_from: Option<u32>,
     pub birth_day_to: Option<u32>,
     pub birth_month_to: Option<u32>,
     pub sort_by: Option<Vec<String>>,
+    /// Language whose alphabet orders text sort keys ("pl", "cs", "sv"...), defaults to plain accent-insensitive order
+    pub sort_locale: Option<String>,
     pub name_query: Option<String>,
-    pub position: Option<String>,
-    pub favourite_club: Option<i32>,
+    /// Match `name_query` by similarity instead of substring, tolerating typos
+    pub fuzzy: Option<bool>,
+    /// Minimum relevance (0-1) for a fuzzy match, defaults to `DEFAULT_FUZZY_THRESHOLD`
+    pub fuzzy_threshold: Option<f64>,
+    pub position: Option<ValueFilter<String>>,
+    pub favourite_club: Option<ValueFilter<i32>>,
+    pub min_height: Option<i32>,
+    pub max_height: Option<i32>,
+    pub min_weight: Option<i32>,
+    pub max_weight: Option<i32>,
+    pub ethnicity: Option<ValueFilter<i8>>,
+    pub skin_tone: Option<ValueFilter<i8>>,
+    pub hair_color: Option<ValueFilter<i8>>,
+    pub record_type: Option<ValueFilter<RecordType>>,
+    pub birth_city: Option<ValueFilter<String>>,
+    pub missing_fields: Option<Vec<OptionalField>>,
+    pub regex_filters: Option<Vec<RegexFilter>>,
+}
+
+/// Text fields of `Player` a regular expression can be applied to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextField {
+    FirstName,
+    CommonName,
+    LastName,
+    /// "first last common", as used by the name search
+    FullName,
+    BirthDate,
+    Position,
+    BirthCity,
+    RecordType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegexFilter {
+    pub field: TextField,
+    pub pattern: String,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Keep players that do *not* match
+    #[serde(default)]
+    pub invert: bool,
+}
+
+/// Include/exclude value sets for a categorical filter.
+/// Accepts a single value (`5`), a list (`[5, 7]`) or `{ "include": [...], "exclude": [...] }`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "ValueFilterInput<T>", bound(deserialize = "T: Deserialize<'de>"))]
+pub struct ValueFilter<T> {
+    pub include: Vec<T>,
+    pub exclude: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValueFilterInput<T> {
+    One(T),
+    Many(Vec<T>),
+    Sets {
+        #[serde(default = "Vec::new")]
+        include: Vec<T>,
+        #[serde(default = "Vec::new")]
+        exclude: Vec<T>,
+    },
+}
+
+impl<T> From<ValueFilterInput<T>> for ValueFilter<T> {
+    fn from(input: ValueFilterInput<T>) -> Self {
+        match input {
+            ValueFilterInput::One(value) => ValueFilter { include: vec![value], exclude: Vec::new() },
+            ValueFilterInput::Many(values) => ValueFilter { include: values, exclude: Vec::new() },
+            ValueFilterInput::Sets { include, exclude } => ValueFilter { include, exclude },
+        }
+    }
+}
+
+impl<T> ValueFilter<T> {
+    /// `is_value` tells whether a filter entry equals the player's value.
+    /// An empty include set lets everything through; a player without a value
+    /// fails a non-empty include set but is never excluded.
+    pub fn matches_by(&self, is_value: impl Fn(&T) -> bool) -> bool {
+        (self.include.is_empty() || self.include.iter().any(&is_value))
+            && !self.exclude.iter().any(&is_value)
+    }
+}
+
+impl<T: PartialEq> ValueFilter<T> {
+    pub fn matches(&self, value: Option<&T>) -> bool {
+        self.matches_by(|candidate| value == Some(candidate))
+    }
+}
+
+/// Optional `Player` fields that can be checked for a missing value
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionalField {
+    CommonName,
+    FavouriteTeam,
+    PreferredFoot,
+    Position,
+    FavouriteNumber,
+    BirthCity,
+    Ca,
+    Pa,
+    Club,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryInfo {
+    pub handle: String,
+    pub total: usize,
+    pub version: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlayersPage {
+    pub handle: String,
+    pub records: Vec<PlayerRecord>,
+    pub offset: usize,
+    pub limit: usize,
+    pub total: usize,
+    /// 1-based page number of `offset`
+    pub page: usize,
+    pub page_count: usize,
+    /// `PLAYERS` version the ordering belongs to; changes when the result set was rebuilt
+    pub version: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -99,6 +236,379 @@ pub struct TopPlayers {
     pub top_lightest: Vec<PlayerRecord>,
 }
 
+/// Numeric player values (stored or derived) the statistics commands work on
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NumericField {
+    Ca,
+    Pa,
+    Height,
+    Weight,
+    FavouriteNumber,
+    BirthYear,
+    /// Age at the configured reference date
+    GameAge,
+    /// PA minus CA
+    PaCaGap,
+    /// Weight (kg) / height (m) squared
+    Bmi,
+}
+
+/// Player attributes a selection can be grouped by
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupDimension {
+    Nationality,
+    Club,
+    FavouriteTeam,
+    Position,
+    PreferredFoot,
+    Ethnicity,
+    SkinTone,
+    HairColor,
+    RecordType,
+    BirthCity,
+    BirthYear,
+    BirthMonth,
+    /// Age at the configured reference date
+    GameAge,
+    /// CA rounded down to a multiple of the band width
+    CaBand,
+    /// PA rounded down to a multiple of the band width
+    PaBand,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregateFunction {
+    Count,
+    Min,
+    Max,
+    Mean,
+    Median,
+    Sum,
+}
+
+/// One aggregate column; `count` without a field counts players
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    #[serde(default)]
+    pub field: Option<NumericField>,
+}
+
+/// A group value; `key` is `None` for players missing the attribute
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GroupKey {
+    pub key: Option<String>,
+    pub label: String,
+}
+
+/// Aggregates of one (row, column) group
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupStatsCell {
+    /// Index into `GroupStatsTable::rows`
+    pub row: usize,
+    /// Index into `GroupStatsTable::columns`, `None` when grouping by one dimension
+    pub column: Option<usize>,
+    pub count: usize,
+    /// One value per aggregate, `None` when the group has no values for its field
+    pub values: Vec<Option<f64>>,
+}
+
+/// Pivot table: rows are the first dimension, columns the optional second one.
+/// Only non-empty groups have a cell.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupStatsTable {
+    pub group_by: Vec<GroupDimension>,
+    /// Column labels of `GroupStatsCell::values`, e.g. "count" or "mean_pa"
+    pub aggregates: Vec<String>,
+    pub rows: Vec<GroupKey>,
+    pub columns: Vec<GroupKey>,
+    pub cells: Vec<GroupStatsCell>,
+}
+
+/// One histogram bar: values in `start..end` (the last bin also includes `end`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistogramBin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+    /// Players in the bin, so a chart bar can be turned into an ID filter
+    pub player_ids: Vec<usize>,
+}
+
+/// Bins of the players in one group of the split dimension
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistogramSeries {
+    pub group: GroupKey,
+    pub bins: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Histogram {
+    pub field: NumericField,
+    pub min: f64,
+    pub max: f64,
+    pub bin_width: f64,
+    /// Bins over every player that has the field
+    pub bins: Vec<HistogramBin>,
+    /// Same bins per group when split by a dimension, empty otherwise
+    pub series: Vec<HistogramSeries>,
+    /// Players without a value for the field
+    pub missing: usize,
+}
+
+/// Least-squares line `y = slope * x + intercept`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegressionLine {
+    pub slope: f64,
+    pub intercept: f64,
+    /// Unset when y is constant, since the correlation is then undefined
+    pub r_squared: Option<f64>,
+}
+
+/// One non-empty cell of the scatter grid
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScatterCell {
+    pub x_start: f64,
+    pub x_end: f64,
+    pub y_start: f64,
+    pub y_end: f64,
+    pub count: usize,
+    pub mean_x: f64,
+    pub mean_y: f64,
+}
+
+/// How two numeric fields relate across a selection. Only players with both
+/// values take part; correlations are `None` when either field is constant.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelationshipStats {
+    pub x: NumericField,
+    pub y: NumericField,
+    pub count: usize,
+    /// Pearson (linear) correlation
+    pub pearson: Option<f64>,
+    /// Spearman (rank) correlation, robust to outliers and curved relationships
+    pub spearman: Option<f64>,
+    pub regression: Option<RegressionLine>,
+    pub scatter: Vec<ScatterCell>,
+}
+
+/// A calendar day with far more births than an even spread would give,
+/// typically left over from placeholder dates such as 01/01
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DateCluster {
+    pub day: u32,
+    pub month: u32,
+    pub count: usize,
+    /// Births expected on that day if birthdays were spread evenly
+    pub expected: f64,
+}
+
+/// Birth-date distribution of a group of players
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BirthCalendar {
+    /// Players with a valid birth date
+    pub total: usize,
+    /// 366 entries on a leap-year calendar (index 59 is 29/02), so days line up across years
+    pub day_of_year_counts: Vec<usize>,
+    /// Births per ISO 8601 week (index 0 is week 1); week 53 only exists in some years
+    pub week_counts: Vec<usize>,
+    /// Births per quarter (Jan-Mar, Apr-Jun, Jul-Sep, Oct-Dec) for each birth year
+    pub quarter_counts: std::collections::BTreeMap<i32, [usize; 4]>,
+    pub leap_day_count: usize,
+    pub clusters: Vec<DateCluster>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NationalityCalendar {
+    pub nationality_id: i32,
+    pub calendar: BirthCalendar,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BirthSeasonality {
+    pub overall: BirthCalendar,
+    /// Per nationality, most players first; empty unless requested
+    pub by_nationality: Vec<NationalityCalendar>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeaderboardEntry {
+    /// 1-based rank within the group; tied values share a rank ("1, 2, 2, 4")
+    pub rank: usize,
+    pub value: f64,
+    pub record: PlayerRecord,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeaderboardGroup {
+    /// `None` for an ungrouped leaderboard
+    pub group: Option<GroupKey>,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Leaderboard {
+    pub metric: NumericField,
+    pub direction: SortDirection,
+    pub relative_to_age: bool,
+    pub groups: Vec<LeaderboardGroup>,
+}
+
+/// Required number of players whose `field` falls within `min..=max`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BandTarget {
+    pub field: NumericField,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_count: Option<usize>,
+    pub max_count: Option<usize>,
+}
+
+/// One design target, e.g. "each top-20 nation gets 8-12 regens per birth year
+/// with at least two at 160+ PA": `filters` selects the players, `group_by`
+/// splits them and every group is checked against the counts and bands.
+/// Nations, clubs, positions and birth years listed in the filters are expected
+/// even when no player has them yet.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BalanceTarget {
+    pub name: String,
+    #[serde(default)]
+    pub filters: Option<PlayerFilters>,
+    #[serde(default)]
+    pub group_by: Vec<GroupDimension>,
+    pub min_count: Option<usize>,
+    pub max_count: Option<usize>,
+    #[serde(default)]
+    pub bands: Vec<BandTarget>,
+}
+
+/// Contents of a target-spec file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BalanceSpec {
+    pub targets: Vec<BalanceTarget>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BalanceIssue {
+    pub target: String,
+    /// One key per `group_by` dimension
+    pub group: Vec<GroupKey>,
+    /// "count" or the band, e.g. "pa 160+"
+    pub check: String,
+    pub actual: usize,
+    pub expected_min: Option<usize>,
+    pub expected_max: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BalanceAudit {
+    pub checks: usize,
+    pub passed: usize,
+    pub shortfalls: Vec<BalanceIssue>,
+    pub surpluses: Vec<BalanceIssue>,
+    /// 0-100; every failed check costs in proportion to how far it is off target
+    pub health_score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupCount {
+    pub group: GroupKey,
+    pub count: usize,
+}
+
+/// Regens arriving at the youth intake of one in-game season
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeasonIntake {
+    /// In-game year the season starts in
+    pub season: i32,
+    /// "2031/32"
+    pub label: String,
+    /// Matching year of the mod's real-world timeline
+    pub real_year: i32,
+    pub count: usize,
+    pub by_nationality: Vec<GroupCount>,
+    pub by_club: Vec<GroupCount>,
+    pub by_position: Vec<GroupCount>,
+    pub by_pa_band: Vec<GroupCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TalentPipeline {
+    /// Consecutive seasons from the first one of the save to the last arrival
+    pub seasons: Vec<SeasonIntake>,
+    /// Seasons in that range without a single arrival
+    pub empty_seasons: Vec<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegenIssueKind {
+    /// Already at the regen age limit on the game start date
+    TooOld,
+    /// Reaches the youth intake age only after the save's last season
+    TooYoung,
+}
+
+/// Future regen whose birth date falls outside the save's window
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegenIssue {
+    pub kind: RegenIssueKind,
+    pub age_at_start: i32,
+    /// Birth date after shifting the year into the valid window
+    pub suggested_birth_date: Date,
+    pub record: PlayerRecord,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegenEligibility {
+    pub game_start: Date,
+    pub first_season: i32,
+    pub last_season: i32,
+    /// Future regens checked
+    pub checked: usize,
+    pub too_old: usize,
+    pub too_young: usize,
+    pub issues: Vec<RegenIssue>,
+}
+
+/// Analyst charts that can be rendered to SVG on the backend
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartKind {
+    /// CA and PA histograms side by side
+    AbilityDistribution,
+    /// Players per nation, largest first
+    Nationalities,
+    /// Players per birth year
+    BirthYears,
+    /// Height against weight, binned, with the regression line
+    HeightWeight,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filters: PlayerFilters,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct InvalidRow {
     pub row_number: usize,
@@ -114,4 +624,201 @@ impl fmt::Display for RecordType {
         };
         write!(f, "{}", value)
     }
-}
\ No newline at end of file
+}
+
+/// Day and month the save starts on; the first season starts in `game_year - 1`
+const GAME_START_DAY: u32 = 1;
+const GAME_START_MONTH: u32 = 7;
+
+/// Calendar date, checked to exist when parsed and always written as DD/MM/YYYY
+/// like the editor files. Orders chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// `None` when the day doesn't exist in that month and year
+    pub fn new(day: u32, month: u32, year: i32) -> Option<Date> {
+        (day >= 1 && day <= days_in_month(month, year)?).then_some(Date { year, month, day })
+    }
+
+    /// Parse DD/MM/YYYY, also accepting single-digit days and months, "." or "-"
+    /// separators and ISO YYYY-MM-DD
+    pub fn parse(text: &str) -> Result<Date, String> {
+        let invalid = || format!("Invalid date: {}", text);
+        let parts: Vec<&str> = text.trim().split(['/', '.', '-']).map(str::trim).collect();
+        let (day, month, year) = match parts.as_slice() {
+            [year, month, day] if year.len() == 4 => (day, month, year),
+            [day, month, year] if year.len() == 4 => (day, month, year),
+            _ => return Err(invalid()),
+        };
+        let number = |part: &str| -> Option<u32> {
+            (!part.is_empty() && part.chars().all(|c| c.is_ascii_digit())).then(|| part.parse().ok())?
+        };
+        match (number(day), number(month), number(year)) {
+            (Some(day), Some(month), Some(year)) => Date::new(day, month, year as i32).ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Start date of a save for the given game year
+    pub fn game_start(game_year: i32) -> Date {
+        Date {
+            year: game_year - 1,
+            month: GAME_START_MONTH,
+            day: GAME_START_DAY,
+        }
+    }
+
+    pub fn day(self) -> u32 {
+        self.day
+    }
+
+    pub fn month(self) -> u32 {
+        self.month
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    /// Move by whole years; 29 February becomes the 28th in common years
+    pub fn shift_years(self, years: i32) -> Date {
+        let year = self.year + years;
+        let day = days_in_month(self.month, year).map_or(self.day, |last| self.day.min(last));
+        Date { year, day, ..self }
+    }
+
+    /// Days since 1 January 1970, negative before it
+    fn days_since_epoch(self) -> i64 {
+        // Days from civil (proleptic Gregorian), with years starting in March
+        let year = if self.month <= 2 { self.year as i64 - 1 } else { self.year as i64 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// ISO weekday, 1 for Monday to 7 for Sunday
+    pub fn weekday(self) -> u32 {
+        // 1 January 1970 was a Thursday
+        ((self.days_since_epoch() + 3).rem_euclid(7) + 1) as u32
+    }
+
+    /// ISO 8601 week number (1-53). Early January days may belong to the last
+    /// week of the previous year, late December days to week 1 of the next.
+    pub fn iso_week(self) -> u32 {
+        fn weeks_in_year(year: i32) -> i64 {
+            let first_day = Date { year, month: 1, day: 1 }.weekday();
+            if first_day == 4 || (first_day == 3 && is_leap_year(year)) { 53 } else { 52 }
+        }
+        let ordinal = self.days_since_epoch() - Date { year: self.year, month: 1, day: 1 }.days_since_epoch() + 1;
+        let week = (ordinal - self.weekday() as i64 + 10) / 7;
+        if week < 1 {
+            weeks_in_year(self.year - 1) as u32
+        } else if week > weeks_in_year(self.year) {
+            1
+        } else {
+            week as u32
+        }
+    }
+
+    /// Completed years from this date to `reference`
+    pub fn age_on(self, reference: Date) -> i32 {
+        let had_birthday = (reference.month, reference.day) >= (self.month, self.day);
+        reference.year - self.year - if had_birthday { 0 } else { 1 }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}/{:02}/{:04}", self.day, self.month, self.year)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Date, String> {
+        Date::parse(s)
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Date::parse(&text).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32, month: u32, year: i32) -> Date {
+        Date::new(day, month, year).unwrap()
+    }
+
+    #[test]
+    fn parse_rejects_days_that_do_not_exist() {
+        assert!(Date::parse("31/02/2008").is_err());
+        assert!(Date::parse("29/02/2007").is_err());
+        assert_eq!(Date::parse("29/02/2008"), Ok(date(29, 2, 2008)));
+    }
+
+    #[test]
+    fn parse_accepts_other_layouts() {
+        assert_eq!(Date::parse("1/2/2007"), Date::parse("01/02/2007"));
+        assert_eq!(Date::parse("01.02.2007"), Ok(date(1, 2, 2007)));
+        assert_eq!(Date::parse("2007-02-01"), Ok(date(1, 2, 2007)));
+        assert!(Date::parse("01/02/07").is_err());
+        assert!(Date::parse("+1/02/2007").is_err());
+    }
+
+    #[test]
+    fn shift_years_clamps_leap_day() {
+        assert_eq!(date(29, 2, 2008).shift_years(1), date(28, 2, 2009));
+        assert_eq!(date(29, 2, 2008).shift_years(4), date(29, 2, 2012));
+    }
+
+    #[test]
+    fn age_counts_completed_years() {
+        let start = Date::game_start(2024);
+        assert_eq!(start, date(1, 7, 2023));
+        assert_eq!(date(1, 7, 2007).age_on(start), 16);
+        assert_eq!(date(2, 7, 2007).age_on(start), 15);
+    }
+
+    #[test]
+    fn iso_weeks_cross_year_ends() {
+        assert_eq!(date(1, 1, 2021).iso_week(), 53);
+        assert_eq!(date(4, 1, 2021).iso_week(), 1);
+        assert_eq!(date(31, 12, 2024).iso_week(), 1);
+        assert_eq!(date(28, 12, 2020).iso_week(), 53);
+        assert_eq!(date(1, 1, 1970).weekday(), 4);
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let birth = date(1, 2, 2007);
+        let json = serde_json::to_string(&birth).unwrap();
+        assert_eq!(json, "\"01/02/2007\"");
+        assert_eq!(serde_json::from_str::<Date>(&json).unwrap(), birth);
+        assert!(serde_json::from_str::<Date>("\"31/02/2007\"").is_err());
+
+        // Early years are zero-padded so they parse back
+        let early = date(1, 7, 5);
+        assert_eq!(early.to_string().parse::<Date>(), Ok(early));
+    }
+}
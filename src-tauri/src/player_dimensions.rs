@@ -0,0 +1,206 @@
+use std::cmp::Ordering;
+
+use crate::model::{AggregateFunction, GroupDimension, GroupKey, NumericField, Player};
+use crate::utils::{
+    bmi, club_name, country_name, game_age, get_position_rank, get_position_short,
+    normalize_name, pa_ca_gap,
+};
+
+/// Band width used for CA/PA bands when the request doesn't set one
+pub const DEFAULT_BAND_WIDTH: i32 = 10;
+
+/// Value of a player in one group-by dimension
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GroupValue {
+    Number(i64),
+    Text(String),
+    Missing,
+}
+
+impl NumericField {
+    pub fn name(self) -> &'static str {
+        match self {
+            NumericField::Ca => "ca",
+            NumericField::Pa => "pa",
+            NumericField::Height => "height",
+            NumericField::Weight => "weight",
+            NumericField::FavouriteNumber => "favourite_number",
+            NumericField::BirthYear => "birth_year",
+            NumericField::GameAge => "game_age",
+            NumericField::PaCaGap => "pa_ca_gap",
+            NumericField::Bmi => "bmi",
+        }
+    }
+
+    /// Value of the field; unset CA/PA, zero height/weight and ages without a
+    /// reference date count as missing
+    pub fn value(self, player: &Player) -> Option<f64> {
+        match self {
+            NumericField::Ca => player.ca.map(f64::from),
+            NumericField::Pa => player.pa.map(f64::from),
+            NumericField::Height => (player.height > 0).then_some(player.height as f64),
+            NumericField::Weight => (player.weight > 0).then_some(player.weight as f64),
+            NumericField::FavouriteNumber => player.favourite_number.map(f64::from),
+            NumericField::BirthYear => Some(player.birth_date.year() as f64),
+            NumericField::GameAge => game_age(player).map(f64::from),
+            NumericField::PaCaGap => pa_ca_gap(player).map(f64::from),
+            NumericField::Bmi => bmi(player),
+        }
+    }
+}
+
+impl AggregateFunction {
+    pub fn name(self) -> &'static str {
+        match self {
+            AggregateFunction::Count => "count",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+            AggregateFunction::Mean => "mean",
+            AggregateFunction::Median => "median",
+            AggregateFunction::Sum => "sum",
+        }
+    }
+
+    /// Apply the function to the values of one group, `None` for an empty group
+    pub fn apply(self, values: &mut [f64]) -> Option<f64> {
+        if values.is_empty() {
+            return match self {
+                AggregateFunction::Count | AggregateFunction::Sum => Some(0.0),
+                _ => None,
+            };
+        }
+        let len = values.len();
+        let result = match self {
+            AggregateFunction::Count => len as f64,
+            AggregateFunction::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            AggregateFunction::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            AggregateFunction::Sum => values.iter().sum(),
+            AggregateFunction::Mean => values.iter().sum::<f64>() / len as f64,
+            AggregateFunction::Median => {
+                values.sort_by(f64::total_cmp);
+                if len.is_multiple_of(2) {
+                    (values[len / 2 - 1] + values[len / 2]) / 2.0
+                } else {
+                    values[len / 2]
+                }
+            }
+        };
+        Some(result)
+    }
+}
+
+fn number(value: Option<i32>) -> GroupValue {
+    value.map_or(GroupValue::Missing, |v| GroupValue::Number(v as i64))
+}
+
+fn band(value: Option<i32>, band_width: i32) -> GroupValue {
+    number(value.map(|v| v.div_euclid(band_width) * band_width))
+}
+
+impl GroupDimension {
+    /// Heading for tables and charts
+    pub fn title(self) -> &'static str {
+        match self {
+            GroupDimension::Nationality => "Nationality",
+            GroupDimension::Club => "Club",
+            GroupDimension::FavouriteTeam => "Favourite team",
+            GroupDimension::Position => "Position",
+            GroupDimension::PreferredFoot => "Preferred foot",
+            GroupDimension::Ethnicity => "Ethnicity",
+            GroupDimension::SkinTone => "Skin tone",
+            GroupDimension::HairColor => "Hair color",
+            GroupDimension::RecordType => "Record type",
+            GroupDimension::BirthCity => "Birth city",
+            GroupDimension::BirthYear => "Birth year",
+            GroupDimension::BirthMonth => "Birth month",
+            GroupDimension::GameAge => "Age",
+            GroupDimension::CaBand => "CA band",
+            GroupDimension::PaBand => "PA band",
+        }
+    }
+
+    pub fn value(self, player: &Player, band_width: i32) -> GroupValue {
+        match self {
+            GroupDimension::Nationality => number(Some(player.nationality_id)),
+            GroupDimension::Club => number(player.club_id),
+            GroupDimension::FavouriteTeam => number(player.favourite_team_id),
+            GroupDimension::Position => match player.position.as_deref() {
+                Some(position) if !position.is_empty() => GroupValue::Text(position.to_string()),
+                _ => GroupValue::Missing,
+            },
+            GroupDimension::PreferredFoot => number(player.preferred_foot.map(i32::from)),
+            GroupDimension::Ethnicity => number(Some(player.ethnicity as i32)),
+            GroupDimension::SkinTone => number(Some(player.skin_tone as i32)),
+            GroupDimension::HairColor => number(Some(player.hair_color as i32)),
+            GroupDimension::RecordType => GroupValue::Text(player.record_type.to_string()),
+            GroupDimension::BirthCity => match player.birth_city.as_deref().map(str::trim) {
+                Some(city) if !city.is_empty() => GroupValue::Text(city.to_string()),
+                _ => GroupValue::Missing,
+            },
+            GroupDimension::BirthYear => number(Some(player.birth_date.year())),
+            GroupDimension::BirthMonth => number(Some(player.birth_date.month() as i32)),
+            GroupDimension::GameAge => number(game_age(player)),
+            GroupDimension::CaBand => band(player.ca, band_width),
+            GroupDimension::PaBand => band(player.pa, band_width),
+        }
+    }
+
+    /// Human readable label: country and club names, short position codes,
+    /// "140-149" for bands
+    pub fn label(self, value: &GroupValue, band_width: i32) -> String {
+        let id = match value {
+            GroupValue::Missing => return "Unknown".to_string(),
+            GroupValue::Text(text) if self == GroupDimension::Position => {
+                return get_position_short(text).unwrap_or(text).to_string();
+            }
+            GroupValue::Text(text) => return text.clone(),
+            GroupValue::Number(n) => *n,
+        };
+        match self {
+            GroupDimension::Nationality => country_name(id as i32)
+                .map_or_else(|| format!("Country {}", id), str::to_string),
+            GroupDimension::Club | GroupDimension::FavouriteTeam => club_name(id as i32)
+                .map_or_else(|| format!("Club {}", id), str::to_string),
+            GroupDimension::CaBand | GroupDimension::PaBand => format!("{}-{}", id, id + band_width as i64 - 1),
+            _ => id.to_string(),
+        }
+    }
+
+    pub fn key(self, value: &GroupValue, band_width: i32) -> GroupKey {
+        GroupKey {
+            key: match value {
+                GroupValue::Number(n) => Some(n.to_string()),
+                GroupValue::Text(text) => Some(text.clone()),
+                GroupValue::Missing => None,
+            },
+            label: self.label(value, band_width),
+        }
+    }
+
+    /// Display order of group values: names alphabetically, positions from
+    /// goalkeeper forward, numbers ascending, missing values last
+    pub fn compare(self, a: &GroupValue, b: &GroupValue, band_width: i32) -> Ordering {
+        match (a, b) {
+            (GroupValue::Missing, GroupValue::Missing) => Ordering::Equal,
+            (GroupValue::Missing, _) => Ordering::Greater,
+            (_, GroupValue::Missing) => Ordering::Less,
+            _ => match self {
+                GroupDimension::Nationality | GroupDimension::Club | GroupDimension::FavouriteTeam
+                | GroupDimension::BirthCity => {
+                    normalize_name(&self.label(a, band_width)).cmp(&normalize_name(&self.label(b, band_width)))
+                }
+                GroupDimension::Position => match (a, b) {
+                    (GroupValue::Text(a), GroupValue::Text(b)) => {
+                        get_position_rank(a).cmp(&get_position_rank(b)).then_with(|| a.cmp(b))
+                    }
+                    _ => Ordering::Equal,
+                },
+                _ => match (a, b) {
+                    (GroupValue::Number(a), GroupValue::Number(b)) => a.cmp(b),
+                    (GroupValue::Text(a), GroupValue::Text(b)) => a.cmp(b),
+                    _ => Ordering::Equal,
+                },
+            },
+        }
+    }
+}
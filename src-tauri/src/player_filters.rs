@@ -0,0 +1,396 @@
+use std::collections::BTreeMap;
+
+use crate::get_player_index;
+use regex::{Regex, RegexBuilder};
+
+use crate::model::{OptionalField, Player, PlayerFilters, PlayerRecord, RegexFilter, TextField};
+use crate::search_query::SearchQuery;
+use crate::utils::{
+    game_age, matches_search_key, name_relevance,
+    player_search_key, is_birth_date_in_range, normalize_name,
+};
+
+/// Check a numeric value against an optional inclusive range.
+/// A missing value never passes once either bound is set.
+fn in_range<T: PartialOrd>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+    if min.is_none() && max.is_none() {
+        return true;
+    }
+    let value = match value {
+        Some(v) => v,
+        None => return false,
+    };
+    if let Some(min) = min {
+        if value < min {
+            return false;
+        }
+    }
+    if let Some(max) = max {
+        if value > max {
+            return false;
+        }
+    }
+    true
+}
+
+/// Minimum relevance for a fuzzy name match when the filters don't set one
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.75;
+
+/// Name query check against a precomputed search key, honouring fuzzy mode
+fn name_matches(search_key: &str, normalized_query: &str, f: &PlayerFilters) -> bool {
+    if f.fuzzy.unwrap_or(false) {
+        name_relevance(search_key, normalized_query) >= f.fuzzy_threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD)
+    } else {
+        matches_search_key(search_key, normalized_query)
+    }
+}
+
+/// Value of a text field; missing optional fields read as an empty string
+fn text_field(player: &Player, field: TextField) -> String {
+    match field {
+        TextField::FirstName => player.first_name.clone(),
+        TextField::CommonName => player.common_name.clone().unwrap_or_default(),
+        TextField::LastName => player.last_name.clone(),
+        TextField::FullName => format!(
+            "{} {} {}",
+            player.first_name,
+            player.last_name,
+            player.common_name.as_deref().unwrap_or("")
+        ),
+        TextField::BirthDate => player.birth_date.to_string(),
+        TextField::Position => player.position.clone().unwrap_or_default(),
+        TextField::BirthCity => player.birth_city.clone().unwrap_or_default(),
+        TextField::RecordType => player.record_type.to_string(),
+    }
+}
+
+/// Regex filter compiled once per query
+pub struct CompiledRegex {
+    field: TextField,
+    regex: Regex,
+    invert: bool,
+}
+
+impl CompiledRegex {
+    fn matches(&self, player: &Player) -> bool {
+        self.regex.is_match(&text_field(player, self.field)) != self.invert
+    }
+}
+
+/// Compile the regex filters, reporting the first invalid pattern
+pub fn compile_regex_filters(filters: &[RegexFilter]) -> Result<Vec<CompiledRegex>, String> {
+    filters
+        .iter()
+        .map(|filter| {
+            let regex = RegexBuilder::new(&filter.pattern)
+                .case_insensitive(filter.case_insensitive)
+                .build()
+                .map_err(|e| format!("Invalid regular expression \"{}\": {}", filter.pattern, e))?;
+            Ok(CompiledRegex {
+                field: filter.field,
+                regex,
+                invert: filter.invert,
+            })
+        })
+        .collect()
+}
+
+fn normalize_city(city: &str) -> String {
+    normalize_name(city.trim())
+}
+
+fn is_field_missing(player: &Player, field: OptionalField) -> bool {
+    match field {
+        OptionalField::CommonName => player.common_name.as_deref().is_none_or(str::is_empty),
+        OptionalField::FavouriteTeam => player.favourite_team_id.is_none(),
+        OptionalField::PreferredFoot => player.preferred_foot.is_none(),
+        OptionalField::Position => player.position.as_deref().is_none_or(str::is_empty),
+        OptionalField::FavouriteNumber => player.favourite_number.is_none(),
+        OptionalField::BirthCity => player.birth_city.as_deref().is_none_or(str::is_empty),
+        OptionalField::Ca => player.ca.is_none(),
+        OptionalField::Pa => player.pa.is_none(),
+        OptionalField::Club => player.club_id.is_none(),
+    }
+}
+
+/// Filters ready to be checked against many players; the name query is parsed
+/// and the regular expressions compiled once here instead of for every player
+pub struct PreparedFilters<'a> {
+    filters: &'a PlayerFilters,
+    query: Option<SearchQuery>,
+    regexes: Vec<CompiledRegex>,
+}
+
+impl<'a> PreparedFilters<'a> {
+    /// Fails when the filters contain an invalid regular expression or a
+    /// fuzzy threshold outside 0-1
+    pub fn new(filters: &'a PlayerFilters) -> Result<PreparedFilters<'a>, String> {
+        if let Some(threshold) = filters.fuzzy_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(format!("Fuzzy threshold must be between 0 and 1, got {}", threshold));
+            }
+        }
+        Ok(PreparedFilters {
+            filters,
+            query: filters.name_query.as_deref().map(SearchQuery::parse),
+            regexes: compile_regex_filters(filters.regex_filters.as_deref().unwrap_or_default())?,
+        })
+    }
+}
+
+/// Single source of truth for `PlayerFilters`.
+/// Every command that accepts filters must go through this function so listing,
+/// statistics and saving always agree on which players match.
+/// `search_key` is the player's precomputed name key when the index has one.
+pub fn matches_filters(id: usize, player: &Player, prepared: &PreparedFilters, search_key: Option<&str>) -> bool {
+    let f = prepared.filters;
+
+    // Player IDs filter (for selected players)
+    if let Some(ref ids) = f.player_ids {
+        if !ids.contains(&id) {
+            return false;
+        }
+    }
+
+    // Name filter
+    if let Some(ref query) = prepared.query {
+        let matched = query.matches_fields(player) && match search_key {
+            Some(key) => name_matches(key, &query.name, f),
+            None => name_matches(&player_search_key(player), &query.name, f),
+        };
+        if !matched {
+            return false;
+        }
+    }
+
+    // Categorical filters (include/exclude sets)
+    if let Some(ref countries) = f.country {
+        if !countries.matches(Some(&player.nationality_id)) {
+            return false;
+        }
+    }
+    if let Some(ref clubs) = f.club {
+        if !clubs.matches(player.club_id.as_ref()) {
+            return false;
+        }
+    }
+    if let Some(ref fav_clubs) = f.favourite_club {
+        if !fav_clubs.matches(player.favourite_team_id.as_ref()) {
+            return false;
+        }
+    }
+    if let Some(ref positions) = f.position {
+        if !positions.matches(player.position.as_ref()) {
+            return false;
+        }
+    }
+    if let Some(ref feet) = f.preferred_foot {
+        if !feet.matches(player.preferred_foot.as_ref()) {
+            return false;
+        }
+    }
+    if let Some(ref fav_nums) = f.favourite_number {
+        if !fav_nums.matches(player.favourite_number.as_ref()) {
+            return false;
+        }
+    }
+    if let Some(ref ethnicities) = f.ethnicity {
+        if !ethnicities.matches(Some(&player.ethnicity)) {
+            return false;
+        }
+    }
+    if let Some(ref skin_tones) = f.skin_tone {
+        if !skin_tones.matches(Some(&player.skin_tone)) {
+            return false;
+        }
+    }
+    if let Some(ref hair_colors) = f.hair_color {
+        if !hair_colors.matches(Some(&player.hair_color)) {
+            return false;
+        }
+    }
+    if let Some(ref record_types) = f.record_type {
+        if !record_types.matches(Some(&player.record_type)) {
+            return false;
+        }
+    }
+
+    // Birth city filter (case and accent insensitive)
+    if let Some(ref cities) = f.birth_city {
+        let city = player.birth_city.as_deref().map(normalize_city);
+        if !cities.matches_by(|wanted| city.as_deref() == Some(normalize_city(wanted).as_str())) {
+            return false;
+        }
+    }
+
+    // Numeric range filters
+    if !in_range(player.ca, f.min_ca, f.max_ca)
+        || !in_range(player.pa, f.min_pa, f.max_pa)
+        || !in_range(Some(player.height), f.min_height, f.max_height)
+        || !in_range(Some(player.weight), f.min_weight, f.max_weight)
+    {
+        return false;
+    }
+
+    // Birth year range filter
+    if !in_range(Some(player.birth_date.year()), f.birth_year_min, f.birth_year_max) {
+        return false;
+    }
+
+    // In-game age range filter
+    if !in_range(game_age(player), f.min_age, f.max_age) {
+        return false;
+    }
+
+    // Birth date range filter (day/month within a year)
+    if (f.birth_day_from.is_some() || f.birth_month_from.is_some() ||
+        f.birth_day_to.is_some() || f.birth_month_to.is_some())
+        && !is_birth_date_in_range(
+            player.birth_date,
+            f.birth_day_from,
+            f.birth_month_from,
+            f.birth_day_to,
+            f.birth_month_to,
+        )
+    {
+        return false;
+    }
+
+    // Regular expression filters
+    if !prepared.regexes.iter().all(|regex| regex.matches(player)) {
+        return false;
+    }
+
+    // "Field is missing" checks
+    if let Some(ref fields) = f.missing_fields {
+        if !fields.iter().all(|&field| is_field_missing(player, field)) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// IDs of every player matching `filters`, in ID order.
+/// Indexed filters narrow the candidates first, the rest is checked per player.
+/// Fails only when the filters contain an invalid regular expression.
+pub fn filtered_ids(players: &BTreeMap<usize, Player>, filters: Option<&PlayerFilters>) -> Result<Vec<usize>, String> {
+    let f = match filters {
+        Some(f) => f,
+        None => return Ok(players.keys().copied().collect()),
+    };
+    // The ID list is already applied by the index
+    let mut remaining = f.clone();
+    remaining.player_ids = None;
+    let prepared = PreparedFilters::new(&remaining)?;
+
+    let index = get_player_index().lock().unwrap();
+    let candidates: Box<dyn Iterator<Item = usize>> = match index.candidates(f) {
+        Some(ids) => Box::new(ids.into_iter()),
+        None => Box::new(players.keys().copied()),
+    };
+
+    Ok(candidates
+        .filter(|id| {
+            let player = match players.get(id) {
+                Some(p) => p,
+                None => return false,
+            };
+            matches_filters(*id, player, &prepared, index.search_key(*id))
+        })
+        .collect())
+}
+
+/// Normalized free text of the name query that relevance is scored against,
+/// `None` when the filters have no name search
+pub fn relevance_query(filters: Option<&PlayerFilters>) -> Option<String> {
+    filters
+        .and_then(|f| f.name_query.as_deref())
+        .map(|q| SearchQuery::parse(q).name)
+        .filter(|q| !q.is_empty())
+}
+
+/// Records for `ids` (skipping unknown ones), each carrying its name relevance
+/// when `query` is set
+pub fn records_with_relevance(
+    players: &BTreeMap<usize, Player>,
+    ids: &[usize],
+    query: Option<&str>,
+) -> Vec<PlayerRecord> {
+    let index = get_player_index().lock().unwrap();
+    ids.iter()
+        .filter_map(|&id| players.get(&id).map(|player| PlayerRecord {
+            id,
+            player: player.clone(),
+            relevance: query.map(|q| match index.search_key(id) {
+                Some(key) => name_relevance(key, q),
+                None => name_relevance(&player_search_key(player), q),
+            }),
+        }))
+        .collect()
+}
+
+/// Collect every player matching `filters`, in ID order.
+/// Without filters all players are returned. With a name query each record
+/// carries its name relevance so results can be ranked.
+pub fn filter_players(
+    players: &BTreeMap<usize, Player>,
+    filters: Option<&PlayerFilters>,
+) -> Result<Vec<PlayerRecord>, String> {
+    let ids = filtered_ids(players, filters)?;
+    Ok(records_with_relevance(players, &ids, relevance_query(filters).as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TextField;
+    use crate::test_support::player;
+
+    fn regex_filters(pattern: &str, invert: bool) -> PlayerFilters {
+        PlayerFilters {
+            regex_filters: Some(vec![RegexFilter {
+                field: TextField::LastName,
+                pattern: pattern.to_string(),
+                case_insensitive: true,
+                invert,
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let filters = regex_filters("(unclosed", false);
+        assert!(PreparedFilters::new(&filters).is_err());
+        assert!(filtered_ids(&BTreeMap::new(), Some(&filters)).is_err());
+    }
+
+    #[test]
+    fn fuzzy_threshold_outside_0_1_is_an_error() {
+        for threshold in [f64::NAN, -0.1, 1.5] {
+            let filters = PlayerFilters { fuzzy_threshold: Some(threshold), ..Default::default() };
+            assert!(PreparedFilters::new(&filters).is_err());
+            assert!(filtered_ids(&BTreeMap::new(), Some(&filters)).is_err());
+        }
+        let filters = PlayerFilters { fuzzy_threshold: Some(1.0), ..Default::default() };
+        assert!(PreparedFilters::new(&filters).is_ok());
+    }
+
+    #[test]
+    fn regex_and_inverted_regex() {
+        let p = player("Jan", "Kowalski");
+        let matches = |filters: &PlayerFilters| matches_filters(0, &p, &PreparedFilters::new(filters).unwrap(), None);
+        assert!(matches(&regex_filters("^kow", false)));
+        assert!(!matches(&regex_filters("^kow", true)));
+        assert!(!matches(&regex_filters("ski$x", false)));
+    }
+
+    #[test]
+    fn missing_value_fails_a_set_range() {
+        assert!(in_range(None::<i32>, None, None));
+        assert!(!in_range(None, Some(1), None));
+        assert!(in_range(Some(5), Some(5), Some(5)));
+        assert!(!in_range(Some(6), None, Some(5)));
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+use crate::model::{Player, PlayerFilters, ValueFilter};
+use crate::utils::player_search_key;
+
+/// Secondary indexes over `PLAYERS`.
+/// Must be updated together with `PLAYERS` (lock `PLAYERS` first, then the index).
+#[derive(Default)]
+pub struct PlayerIndex {
+    by_nationality: HashMap<i32, BTreeSet<usize>>,
+    by_club: HashMap<i32, BTreeSet<usize>>,
+    by_position: HashMap<String, BTreeSet<usize>>,
+    by_birth_year: BTreeMap<i32, BTreeSet<usize>>,
+    by_ca: BTreeMap<i32, BTreeSet<usize>>,
+    by_pa: BTreeMap<i32, BTreeSet<usize>>,
+    search_keys: HashMap<usize, String>,
+}
+
+/// Shared operations for the hashed and ordered index maps
+trait IdSets<K> {
+    fn entry_set(&mut self, key: K) -> &mut BTreeSet<usize>;
+    fn remove_id(&mut self, key: &K, id: usize);
+}
+
+impl<K: Hash + Eq> IdSets<K> for HashMap<K, BTreeSet<usize>> {
+    fn entry_set(&mut self, key: K) -> &mut BTreeSet<usize> {
+        self.entry(key).or_default()
+    }
+
+    fn remove_id(&mut self, key: &K, id: usize) {
+        if let Some(ids) = self.get_mut(key) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.remove(key);
+            }
+        }
+    }
+}
+
+impl<K: Ord> IdSets<K> for BTreeMap<K, BTreeSet<usize>> {
+    fn entry_set(&mut self, key: K) -> &mut BTreeSet<usize> {
+        self.entry(key).or_default()
+    }
+
+    fn remove_id(&mut self, key: &K, id: usize) {
+        if let Some(ids) = self.get_mut(key) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.remove(key);
+            }
+        }
+    }
+}
+
+/// Union of the ID sets stored under each included value
+fn ids_for_values<K: Hash + Eq>(map: &HashMap<K, BTreeSet<usize>>, values: &ValueFilter<K>) -> Option<BTreeSet<usize>> {
+    if values.include.is_empty() {
+        return None;
+    }
+    let mut ids = BTreeSet::new();
+    for value in &values.include {
+        if let Some(set) = map.get(value) {
+            ids.extend(set.iter().copied());
+        }
+    }
+    Some(ids)
+}
+
+/// Union of the ID sets whose key falls within `min..=max`
+fn ids_in_range(map: &BTreeMap<i32, BTreeSet<usize>>, min: Option<i32>, max: Option<i32>) -> Option<BTreeSet<usize>> {
+    if min.is_none() && max.is_none() {
+        return None;
+    }
+    let range: RangeInclusive<i32> = min.unwrap_or(i32::MIN)..=max.unwrap_or(i32::MAX);
+    if range.is_empty() {
+        return Some(BTreeSet::new());
+    }
+    let mut ids = BTreeSet::new();
+    for set in map.range(range).map(|(_, set)| set) {
+        ids.extend(set.iter().copied());
+    }
+    Some(ids)
+}
+
+impl PlayerIndex {
+    pub fn rebuild(&mut self, players: &BTreeMap<usize, Player>) {
+        *self = PlayerIndex::default();
+        for (id, player) in players {
+            self.insert(*id, player);
+        }
+        println!("[INDEX] Indexed {} players", players.len());
+    }
+
+    pub fn insert(&mut self, id: usize, player: &Player) {
+        self.by_nationality.entry_set(player.nationality_id).insert(id);
+        if let Some(club_id) = player.club_id {
+            self.by_club.entry_set(club_id).insert(id);
+        }
+        if let Some(ref position) = player.position {
+            self.by_position.entry_set(position.clone()).insert(id);
+        }
+        self.by_birth_year.entry_set(player.birth_date.year()).insert(id);
+        if let Some(ca) = player.ca {
+            self.by_ca.entry_set(ca).insert(id);
+        }
+        if let Some(pa) = player.pa {
+            self.by_pa.entry_set(pa).insert(id);
+        }
+        self.search_keys.insert(id, player_search_key(player));
+    }
+
+    pub fn remove(&mut self, id: usize, player: &Player) {
+        self.by_nationality.remove_id(&player.nationality_id, id);
+        if let Some(club_id) = player.club_id {
+            self.by_club.remove_id(&club_id, id);
+        }
+        if let Some(ref position) = player.position {
+            self.by_position.remove_id(position, id);
+        }
+        self.by_birth_year.remove_id(&player.birth_date.year(), id);
+        if let Some(ca) = player.ca {
+            self.by_ca.remove_id(&ca, id);
+        }
+        if let Some(pa) = player.pa {
+            self.by_pa.remove_id(&pa, id);
+        }
+        self.search_keys.remove(&id);
+    }
+
+    /// Normalized "first last common" name, precomputed for name searches
+    pub fn search_key(&self, id: usize) -> Option<&str> {
+        self.search_keys.get(&id).map(String::as_str)
+    }
+
+    /// Narrow the filters down to a set of candidate IDs using the indexes.
+    /// Returns `None` when no indexed filter is set and every player has to be scanned.
+    /// Candidates still need a full `matches_filters` check, except for `player_ids`
+    /// which is always applied here.
+    pub fn candidates(&self, f: &PlayerFilters) -> Option<BTreeSet<usize>> {
+        let mut sets: Vec<BTreeSet<usize>> = Vec::new();
+
+        if let Some(ref ids) = f.player_ids {
+            sets.push(ids.iter().copied().collect());
+        }
+        if let Some(ids) = f.country.as_ref().and_then(|c| ids_for_values(&self.by_nationality, c)) {
+            sets.push(ids);
+        }
+        if let Some(ids) = f.club.as_ref().and_then(|c| ids_for_values(&self.by_club, c)) {
+            sets.push(ids);
+        }
+        if let Some(ids) = f.position.as_ref().and_then(|p| ids_for_values(&self.by_position, p)) {
+            sets.push(ids);
+        }
+        if let Some(ids) = ids_in_range(&self.by_birth_year, f.birth_year_min, f.birth_year_max) {
+            sets.push(ids);
+        }
+        if let Some(ids) = ids_in_range(&self.by_ca, f.min_ca, f.max_ca) {
+            sets.push(ids);
+        }
+        if let Some(ids) = ids_in_range(&self.by_pa, f.min_pa, f.max_pa) {
+            sets.push(ids);
+        }
+
+        // Intersect starting from the smallest set
+        sets.sort_by_key(BTreeSet::len);
+        let mut sets = sets.into_iter();
+        let mut result = sets.next()?;
+        for set in sets {
+            result.retain(|id| set.contains(id));
+            if result.is_empty() {
+                break;
+            }
+        }
+        Some(result)
+    }
+}
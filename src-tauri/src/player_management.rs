@@ -1,12 +1,18 @@
 use crate::model::PlayerRecord;
-use crate::get_players;
+use crate::{get_players, get_player_index, bump_players_version};
 
 #[tauri::command]
 pub fn update_players(new_players: Vec<PlayerRecord>) -> Result<(), String> {
     let mut players = get_players().lock().map_err(|e| e.to_string())?;
+    let mut index = get_player_index().lock().map_err(|e| e.to_string())?;
     for record in new_players {
+        if let Some(old_player) = players.get(&record.id) {
+            index.remove(record.id, old_player);
+        }
+        index.insert(record.id, &record.player);
         players.insert(record.id, record.player);
     }
+    bump_players_version();
     Ok(())
 }
 
@@ -23,7 +29,10 @@ pub fn add_new_player() -> Result<usize, String> {
 #[tauri::command]
 pub fn remove_player(id: usize) -> Result<(), String> {
     let mut players = get_players().lock().map_err(|e| e.to_string())?;
-    players.remove(&id);
+    if let Some(player) = players.remove(&id) {
+        get_player_index().lock().map_err(|e| e.to_string())?.remove(id, &player);
+        bump_players_version();
+    }
     println!("Removed player with ID: {}", id);
     Ok(())
 }
@@ -1,165 +1,80 @@
-use crate::model::{PlayerFilters, PlayerRecord};
-use crate::{get_players};
-use crate::utils::{get_birth_year, sort_players, matches_search_query, is_birth_date_in_range};
+use std::collections::BTreeMap;
+
+use crate::model::{Player, PlayerFilters, PlayerRecord, PlayersPage, QueryInfo};
+use crate::{get_players, get_query_cache, players_version};
+use crate::player_filters::{filter_players, records_with_relevance, relevance_query};
+use crate::filter_presets::resolve_filters;
+use crate::query_cache::query_handle;
+use crate::player_sorting::sort_players;
+use crate::collation::CollationLocale;
+
+fn filter_and_sort(players: &BTreeMap<usize, Player>, filters: Option<&PlayerFilters>) -> Result<Vec<PlayerRecord>, String> {
+    let filtered_players = filter_players(players, filters)?;
+
+    println!("Filtered to {} players", filtered_players.len());
+
+    let locale = match filters.and_then(|f| f.sort_locale.as_deref()) {
+        Some(tag) => CollationLocale::parse(tag)?,
+        None => CollationLocale::default(),
+    };
+
+    // Apply sorting - default to birthdate if no sort specified
+    match filters.and_then(|f| f.sort_by.as_ref()) {
+        Some(sort_by) => sort_players(filtered_players, sort_by, locale),
+        None => sort_players(filtered_players, &["age_desc".to_string()], locale),
+    }
+}
 
 #[tauri::command]
-pub fn get_players_chunk(filters: Option<PlayerFilters>) -> Vec<PlayerRecord> {
+pub fn get_players_chunk(filters: Option<PlayerFilters>) -> Result<Vec<PlayerRecord>, String> {
     println!("Getting players chunk with filters");
+    let filters = resolve_filters(filters)?;
     if let Some(ref f) = filters {
         println!("Filters: country={:?}, club={:?}, min_ca={:?}, max_ca={:?}, min_pa={:?}, max_pa={:?}, preferred_foot={:?}, favourite_number={:?}, birth_year_min={:?}, birth_year_max={:?}, sort_by={:?}", 
             f.country, f.club, f.min_ca, f.max_ca, f.min_pa, f.max_pa, f.preferred_foot, f.favourite_number, f.birth_year_min, f.birth_year_max, f.sort_by);
     }
     
     let players = get_players().lock().unwrap();
-    let mut filtered_players: Vec<PlayerRecord> = players
-        .iter()
-        .filter(|(id, player)| {
-            if let Some(ref f) = filters {
-                // Player IDs filter
-                if let Some(ref ids) = f.player_ids {
-                    if !ids.contains(id) {
-                        return false;
-                    }
-                }
-
-                // Name filter
-                if let Some(ref query) = f.name_query {
-                    if !matches_search_query(player, query) {
-                        return false;
-                    }
-                }
-
-                // Country filter
-                if let Some(c) = f.country {
-                    if player.nationality_id != c {
-                        return false;
-                    }
-                }
-                
-                // Club filter
-                if let Some(cid) = f.club {
-                    if player.club_id != Some(cid) {
-                        return false;
-                    }
-                }
-                
-                // CA range filter
-                if let Some(min_ca) = f.min_ca {
-                    if let Some(ca) = player.ca {
-                        if ca < min_ca {
-                            return false;
-                        }
-                    } else {
-                        return false;
-                    }
-                }
-                if let Some(max_ca) = f.max_ca {
-                    if let Some(ca) = player.ca {
-                        if ca > max_ca {
-                            return false;
-                        }
-                    } else {
-                        return false;
-                    }
-                }
-                
-                // PA range filter
-                if let Some(min_pa) = f.min_pa {
-                    if let Some(pa) = player.pa {
-                        if pa < min_pa {
-                            return false;
-                        }
-                    } else {
-                        return false;
-                    }
-                }
-                if let Some(max_pa) = f.max_pa {
-                    if let Some(pa) = player.pa {
-                        if pa > max_pa {
-                            return false;
-                        }
-                    } else {
-                        return false;
-                    }
-                }
-                
-                // Preferred foot filter
-                if let Some(foot) = f.preferred_foot {
-                    if player.preferred_foot != Some(foot) {
-                        return false;
-                    }
-                }
-                
-                // Favourite number filter
-                if let Some(fav_num) = f.favourite_number {
-                    if player.favourite_number != Some(fav_num) {
-                        return false;
-                    }
-                }
-
-                // Position filter
-                if let Some(ref pos) = f.position {
-                    if player.position.as_ref() != Some(pos) {
-                        return false;
-                    }
-                }
-
-                // Favourite club filter
-                if let Some(fav_club) = f.favourite_club {
-                    if player.favourite_team_id != Some(fav_club) {
-                        return false;
-                    }
-                }
-                
-                // Birth year filter
-                if let Some(birth_year) = f.birth_year_min {
-                    if let Some(player_birth_year) = get_birth_year(&player.birth_date) {
-                        if player_birth_year != birth_year {
-                            return false;
-                        }
-                    } else {
-                        println!("Failed to parse birth date: {}", player.birth_date);
-                        return false;
-                    }
-                }
-                
-                // Birth date range filter (day/month within a year)
-                if f.birth_day_from.is_some() || f.birth_month_from.is_some() || 
-                   f.birth_day_to.is_some() || f.birth_month_to.is_some() {
-                    if !is_birth_date_in_range(
-                        &player.birth_date,
-                        f.birth_day_from,
-                        f.birth_month_from,
-                        f.birth_day_to,
-                        f.birth_month_to,
-                    ) {
-                        return false;
-                    }
-                }
-            }
-            true
-        })
-        .map(|(id, player)| PlayerRecord {
-            id: *id,
-            player: player.clone(),
-        })
-        .collect();
+    let filtered_players = filter_and_sort(&players, filters.as_ref())?;
 
-    println!("Filtered to {} players", filtered_players.len());
+    println!("Returning {} players chunk", filtered_players.len());
+    Ok(filtered_players)
+}
 
-    // Apply sorting - default to birthdate if no sort specified
-    if let Some(ref f) = filters {
-        if let Some(ref sort_by) = f.sort_by {
-            filtered_players = sort_players(filtered_players, sort_by);
-        } else {
-            filtered_players = sort_players(filtered_players, &["age_desc".to_string()]);
+/// Sorted IDs for a resolved filter set, served from the query cache while `PLAYERS` is unchanged.
+/// `players` is the locked `PLAYERS` map, so the version matches the data we sort.
+fn cached_query(
+    players: &BTreeMap<usize, Player>,
+    handle: &str,
+    filters: Option<PlayerFilters>,
+) -> Result<(Vec<usize>, u64), String> {
+    let version = players_version();
+
+    {
+        let mut cache = get_query_cache().lock().map_err(|e| e.to_string())?;
+        if let Some(entry) = cache.get_fresh(handle, version) {
+            return Ok((entry.ids.clone(), version));
         }
-    } else {
-        filtered_players = sort_players(filtered_players, &["age_desc".to_string()]);
     }
 
-    println!("Returning {} players chunk", filtered_players.len());
-    filtered_players
+    let ids: Vec<usize> = filter_and_sort(players, filters.as_ref())?
+        .into_iter()
+        .map(|record| record.id)
+        .collect();
+    println!("[QUERY] Cached {} ids for query {} (version {})", ids.len(), handle, version);
+
+    let mut cache = get_query_cache().lock().map_err(|e| e.to_string())?;
+    cache.insert(handle.to_string(), filters, ids.clone(), version);
+    Ok((ids, version))
+}
+
+/// Records for one page of a query, with the name relevance the full result set would carry
+fn records_for_ids(
+    players: &BTreeMap<usize, Player>,
+    ids: &[usize],
+    filters: Option<&PlayerFilters>,
+) -> Vec<PlayerRecord> {
+    records_with_relevance(players, ids, relevance_query(filters).as_deref())
 }
 
 #[tauri::command]
@@ -167,28 +82,86 @@ pub fn get_players_page(
     offset: usize,
     limit: usize,
     filters: Option<PlayerFilters>,
-) -> Vec<PlayerRecord> {
+) -> Result<Vec<PlayerRecord>, String> {
     println!("Getting players page - offset: {}, limit: {}", offset, limit);
     
-    let filtered_players = get_players_chunk(filters);
+    let filters = resolve_filters(filters)?;
+    let handle = query_handle(filters.as_ref());
+    // IDs and records come from the same lock, so a page never mixes data versions
+    let players = get_players().lock().map_err(|e| e.to_string())?;
+    let (ids, _) = cached_query(&players, &handle, filters.clone())?;
 
-    let result: Vec<PlayerRecord> = filtered_players
-        .into_iter()
-        .skip(offset)
-        .take(limit)
-        .collect();
+    let page_ids: Vec<usize> = ids.into_iter().skip(offset).take(limit).collect();
+    let result = records_for_ids(&players, &page_ids, filters.as_ref());
     
     println!("Returning {} players for page", result.len());
-    result
+    Ok(result)
+}
+
+/// Build (or reuse) the sorted result set for `filters` and return a handle to page through it
+#[tauri::command]
+pub fn create_players_query(filters: Option<PlayerFilters>) -> Result<QueryInfo, String> {
+    let filters = resolve_filters(filters)?;
+    let handle = query_handle(filters.as_ref());
+    let players = get_players().lock().map_err(|e| e.to_string())?;
+    let (ids, version) = cached_query(&players, &handle, filters)?;
+
+    Ok(QueryInfo {
+        handle,
+        total: ids.len(),
+        version,
+    })
+}
+
+/// Read one page from a query handle.
+/// If `PLAYERS` changed since the handle was built, the result set is rebuilt and `version` changes.
+/// Handles are dropped once enough newer queries are cached; a dropped handle
+/// fails and the query has to be created again with `create_players_query`.
+#[tauri::command]
+pub fn get_query_page(handle: String, offset: usize, limit: usize) -> Result<PlayersPage, String> {
+    // Lock order: PLAYERS before QUERY_CACHE, and the page is read under the same PLAYERS lock
+    let players = get_players().lock().map_err(|e| e.to_string())?;
+    let filters = get_query_cache()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .filters(&handle)
+        .ok_or_else(|| {
+            format!(
+                "Unknown query handle {}: it was released or evicted by newer queries, create the query again",
+                handle
+            )
+        })?;
+    let (ids, version) = cached_query(&players, &handle, filters.clone())?;
+
+    let total = ids.len();
+    let page_ids: Vec<usize> = ids.into_iter().skip(offset).take(limit).collect();
+    let records = records_for_ids(&players, &page_ids, filters.as_ref());
+
+    Ok(PlayersPage {
+        handle,
+        records,
+        offset,
+        limit,
+        total,
+        page: offset.checked_div(limit).map_or(1, |page| page + 1),
+        page_count: if limit > 0 { total.div_ceil(limit) } else { 0 },
+        version,
+    })
+}
+
+#[tauri::command]
+pub fn release_players_query(handle: String) -> Result<bool, String> {
+    let mut cache = get_query_cache().lock().map_err(|e| e.to_string())?;
+    Ok(cache.remove(&handle))
 }
 
 #[tauri::command]
-pub fn get_filtered_player_ids(filters: Option<PlayerFilters>) -> Vec<usize> {
+pub fn get_filtered_player_ids(filters: Option<PlayerFilters>) -> Result<Vec<usize>, String> {
     println!("Getting filtered player IDs");
-    let filtered_players = get_players_chunk(filters);
+    let filtered_players = get_players_chunk(filters)?;
     
-    filtered_players
+    Ok(filtered_players
         .into_iter()
         .map(|record| record.id)
-        .collect()
+        .collect())
 }
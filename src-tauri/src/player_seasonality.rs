@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::get_players;
+use crate::filter_presets::resolve_filters;
+use crate::model::{BirthCalendar, BirthSeasonality, Date, DateCluster, NationalityCalendar, PlayerFilters};
+use crate::player_filters::filter_players;
+use crate::utils::is_leap_year;
+
+/// Standard deviations above the expected count before a day counts as a cluster
+const DEFAULT_CLUSTER_THRESHOLD: f64 = 4.0;
+/// Days with fewer births than this are never reported as clusters
+const MIN_CLUSTER_SIZE: usize = 5;
+
+/// Days before each month on a leap-year calendar
+const MONTH_OFFSETS: [usize; 12] = [0, 31, 60, 91, 121, 152, 182, 213, 244, 274, 305, 335];
+const LEAP_DAY_INDEX: usize = 59;
+
+/// (day, month) of a leap-year calendar index
+fn calendar_date(index: usize) -> (u32, u32) {
+    let month = MONTH_OFFSETS.iter().rposition(|&offset| offset <= index).unwrap_or(0);
+    ((index - MONTH_OFFSETS[month] + 1) as u32, month as u32 + 1)
+}
+
+/// Accumulates the calendar of one group of players
+struct CalendarBuilder {
+    calendar: BirthCalendar,
+    leap_year_births: usize,
+}
+
+impl CalendarBuilder {
+    fn new() -> CalendarBuilder {
+        CalendarBuilder {
+            calendar: BirthCalendar {
+                day_of_year_counts: vec![0; 366],
+                week_counts: vec![0; 53],
+                ..BirthCalendar::default()
+            },
+            leap_year_births: 0,
+        }
+    }
+
+    fn add(&mut self, date: Date) {
+        let (day, month, year) = (date.day(), date.month(), date.year());
+        let index = MONTH_OFFSETS[month as usize - 1] + day as usize - 1;
+        let calendar = &mut self.calendar;
+        calendar.total += 1;
+        calendar.day_of_year_counts[index] += 1;
+        calendar.week_counts[date.iso_week() as usize - 1] += 1;
+        calendar.quarter_counts.entry(year).or_default()[(month as usize - 1) / 3] += 1;
+        if index == LEAP_DAY_INDEX {
+            calendar.leap_day_count += 1;
+        }
+        if is_leap_year(year) {
+            self.leap_year_births += 1;
+        }
+    }
+
+    /// Flag days whose count is far above an even spread. Players born in
+    /// leap years spread over 366 days, the others over 365 (no 29/02).
+    fn finish(mut self, threshold: f64) -> BirthCalendar {
+        let calendar = &mut self.calendar;
+        let leap = self.leap_year_births as f64;
+        let common = (calendar.total - self.leap_year_births) as f64;
+        let mut clusters: Vec<DateCluster> = calendar
+            .day_of_year_counts
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &count)| {
+                let expected = if index == LEAP_DAY_INDEX { leap / 366.0 } else { leap / 366.0 + common / 365.0 };
+                let abnormal = count >= MIN_CLUSTER_SIZE && count as f64 > expected + threshold * expected.sqrt();
+                abnormal.then(|| {
+                    let (day, month) = calendar_date(index);
+                    DateCluster { day, month, count, expected }
+                })
+            })
+            .collect();
+        clusters.sort_by(|a, b| (b.count as f64 / b.expected).total_cmp(&(a.count as f64 / a.expected)));
+        calendar.clusters = clusters;
+        self.calendar
+    }
+}
+
+/// Birth-date seasonality of a filtered selection: day-of-year and ISO week
+/// distributions, quarter counts per birth year (relative-age effect),
+/// leap-day births and suspicious date clusters, optionally per nationality.
+#[tauri::command]
+pub fn birth_seasonality(
+    filters: Option<PlayerFilters>,
+    by_nationality: Option<bool>,
+    cluster_threshold: Option<f64>,
+) -> Result<BirthSeasonality, String> {
+    println!("Analysing birth date seasonality");
+    let threshold = cluster_threshold.unwrap_or(DEFAULT_CLUSTER_THRESHOLD);
+    if !threshold.is_finite() || threshold < 0.0 {
+        return Err(format!("Cluster threshold must be a non-negative number, got {}", threshold));
+    }
+    let by_nationality = by_nationality.unwrap_or(false);
+    let filters = resolve_filters(filters)?;
+
+    let players = get_players().lock().unwrap();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+
+    let mut overall = CalendarBuilder::new();
+    let mut nations: HashMap<i32, CalendarBuilder> = HashMap::new();
+    for record in &filtered_players {
+        let birth_date = record.player.birth_date;
+        overall.add(birth_date);
+        if by_nationality {
+            nations
+                .entry(record.player.nationality_id)
+                .or_insert_with(CalendarBuilder::new)
+                .add(birth_date);
+        }
+    }
+
+    let mut by_nationality: Vec<NationalityCalendar> = nations
+        .into_iter()
+        .map(|(nationality_id, builder)| NationalityCalendar {
+            nationality_id,
+            calendar: builder.finish(threshold),
+        })
+        .collect();
+    by_nationality.sort_by_key(|nation| (std::cmp::Reverse(nation.calendar.total), nation.nationality_id));
+
+    Ok(BirthSeasonality {
+        overall: overall.finish(threshold),
+        by_nationality,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32, month: u32, year: i32) -> Date {
+        Date::new(day, month, year).unwrap()
+    }
+
+    #[test]
+    fn calendar_index_round_trips() {
+        assert_eq!(calendar_date(0), (1, 1));
+        assert_eq!(calendar_date(LEAP_DAY_INDEX), (29, 2));
+        assert_eq!(calendar_date(60), (1, 3));
+        assert_eq!(calendar_date(365), (31, 12));
+    }
+
+    #[test]
+    fn weeks_are_iso_weeks() {
+        let mut builder = CalendarBuilder::new();
+        // Sunday 1 January 2006 is in week 52 of 2005, Monday 2 January starts week 1
+        builder.add(date(1, 1, 2006));
+        builder.add(date(2, 1, 2006));
+        // Thursday 31 December 2009 is in week 53
+        builder.add(date(31, 12, 2009));
+        let calendar = builder.finish(DEFAULT_CLUSTER_THRESHOLD);
+        assert_eq!(calendar.week_counts.len(), 53);
+        assert_eq!(calendar.week_counts[51], 1);
+        assert_eq!(calendar.week_counts[0], 1);
+        assert_eq!(calendar.week_counts[52], 1);
+    }
+
+    #[test]
+    fn leap_day_and_quarters_are_counted() {
+        let mut builder = CalendarBuilder::new();
+        builder.add(date(29, 2, 2008));
+        builder.add(date(1, 10, 2008));
+        let calendar = builder.finish(DEFAULT_CLUSTER_THRESHOLD);
+        assert_eq!(calendar.leap_day_count, 1);
+        assert_eq!(calendar.quarter_counts[&2008], [1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn invalid_cluster_thresholds_are_rejected() {
+        for threshold in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(birth_seasonality(None, None, Some(threshold)).is_err());
+        }
+    }
+
+    #[test]
+    fn piles_of_one_date_are_clusters() {
+        let mut builder = CalendarBuilder::new();
+        for day in 0..365 {
+            let (d, m) = calendar_date(if day >= LEAP_DAY_INDEX { day + 1 } else { day });
+            builder.add(date(d, m, 2007));
+        }
+        for _ in 0..30 {
+            builder.add(date(1, 1, 2007));
+        }
+        let calendar = builder.finish(DEFAULT_CLUSTER_THRESHOLD);
+        assert_eq!(calendar.clusters.len(), 1);
+        assert_eq!((calendar.clusters[0].day, calendar.clusters[0].month), (1, 1));
+    }
+}
@@ -0,0 +1,319 @@
+use std::cmp::Ordering;
+
+use crate::collation::{collation_key, CollationKey, CollationLocale};
+use crate::model::{Player, PlayerRecord};
+use crate::transliteration::{needs_transliteration, transliterate};
+use crate::utils::{bmi, club_name, country_name, game_age, get_position_rank, pa_ca_gap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Ca,
+    Pa,
+    Age,
+    /// Whole years at the configured reference date; ties fall through to the next key
+    GameAge,
+    Name,
+    NameLatin,
+    FirstName,
+    LastName,
+    CommonName,
+    Club,
+    Nationality,
+    Position,
+    Height,
+    Weight,
+    Foot,
+    FavouriteNumber,
+    FavouriteTeam,
+    BirthCity,
+    Ethnicity,
+    SkinTone,
+    HairColor,
+    RecordType,
+    /// PA minus CA
+    PaCaGap,
+    /// Weight (kg) / height (m) squared
+    Bmi,
+    Relevance,
+}
+
+impl SortField {
+    fn from_name(name: &str) -> Option<SortField> {
+        let field = match name {
+            "ca" => SortField::Ca,
+            "pa" => SortField::Pa,
+            "age" => SortField::Age,
+            "game_age" => SortField::GameAge,
+            "name" => SortField::Name,
+            "name_latin" => SortField::NameLatin,
+            "first_name" => SortField::FirstName,
+            "last_name" => SortField::LastName,
+            "common_name" => SortField::CommonName,
+            "club" => SortField::Club,
+            "nationality" => SortField::Nationality,
+            "position" => SortField::Position,
+            "height" => SortField::Height,
+            "weight" => SortField::Weight,
+            "foot" => SortField::Foot,
+            "favourite_number" => SortField::FavouriteNumber,
+            "favourite_team" => SortField::FavouriteTeam,
+            "birth_city" => SortField::BirthCity,
+            "ethnicity" => SortField::Ethnicity,
+            "skin_tone" => SortField::SkinTone,
+            "hair_color" => SortField::HairColor,
+            "record_type" => SortField::RecordType,
+            "pa_ca_gap" => SortField::PaCaGap,
+            "bmi" => SortField::Bmi,
+            "relevance" => SortField::Relevance,
+            _ => return None,
+        };
+        Some(field)
+    }
+}
+
+/// One parsed sort criterion: `<field>_<asc|desc>[_nulls_first|_nulls_last]`.
+/// Missing values go last unless `_nulls_first` is given. `relevance` on its own
+/// means best matches first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+    pub nulls_first: bool,
+}
+
+impl SortKey {
+    pub fn parse(criterion: &str) -> Result<SortKey, String> {
+        let (rest, nulls_first) = if let Some(rest) = criterion.strip_suffix("_nulls_first") {
+            (rest, true)
+        } else if let Some(rest) = criterion.strip_suffix("_nulls_last") {
+            (rest, false)
+        } else {
+            (criterion, false)
+        };
+
+        let (field, descending) = if rest == "relevance" {
+            ("relevance", true)
+        } else if let Some(field) = rest.strip_suffix("_asc") {
+            (field, false)
+        } else if let Some(field) = rest.strip_suffix("_desc") {
+            (field, true)
+        } else {
+            return Err(format!("Invalid sort key: {} (expected <field>_asc or <field>_desc)", criterion));
+        };
+
+        let field = SortField::from_name(field).ok_or_else(|| format!("Unknown sort field: {}", criterion))?;
+        Ok(SortKey { field, descending, nulls_first })
+    }
+}
+
+/// Comparable value of one sort field; `None` marks a missing value
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum SortValue {
+    Int(i64),
+    Float(f64),
+    Text(CollationKey),
+    /// Position rank, then the raw position string
+    Ranked(i64, String),
+}
+
+impl SortValue {
+    fn compare(&self, other: &SortValue) -> Ordering {
+        match (self, other) {
+            (SortValue::Float(a), SortValue::Float(b)) => a.total_cmp(b),
+            _ => self.partial_cmp(other).unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+fn text(value: Option<&str>, locale: CollationLocale) -> Option<SortValue> {
+    value.filter(|v| !v.is_empty()).map(|v| SortValue::Text(collation_key(v, locale)))
+}
+
+fn positive(value: i32) -> Option<SortValue> {
+    (value > 0).then_some(SortValue::Int(value as i64))
+}
+
+/// Collation key of "first last", optionally transliterated to Latin script
+fn name_sort_key(player: &Player, latin: bool, locale: CollationLocale) -> CollationKey {
+    let name = format!("{} {}", player.first_name, player.last_name);
+    if latin && needs_transliteration(&name) {
+        collation_key(&transliterate(&name), locale)
+    } else {
+        collation_key(&name, locale)
+    }
+}
+
+fn sort_value(field: SortField, record: &PlayerRecord, locale: CollationLocale) -> Option<SortValue> {
+    let player = &record.player;
+    match field {
+        SortField::Ca => player.ca.map(|v| SortValue::Int(v as i64)),
+        SortField::Pa => player.pa.map(|v| SortValue::Int(v as i64)),
+        // Birth date as YYYYMMDD; `age` flips the direction below
+        SortField::Age => {
+            let date = player.birth_date;
+            Some(SortValue::Int(date.year() as i64 * 10_000 + date.month() as i64 * 100 + date.day() as i64))
+        }
+        SortField::GameAge => game_age(player).map(|v| SortValue::Int(v as i64)),
+        SortField::Name => Some(SortValue::Text(name_sort_key(player, false, locale))),
+        SortField::NameLatin => Some(SortValue::Text(name_sort_key(player, true, locale))),
+        SortField::FirstName => text(Some(&player.first_name), locale),
+        SortField::LastName => text(Some(&player.last_name), locale),
+        SortField::CommonName => text(player.common_name.as_deref(), locale),
+        SortField::Club => text(player.club_id.and_then(club_name), locale),
+        SortField::Nationality => text(country_name(player.nationality_id), locale),
+        SortField::Position => player.position.as_deref()
+            .filter(|p| !p.is_empty())
+            .map(|p| SortValue::Ranked(get_position_rank(p) as i64, p.to_string())),
+        SortField::Height => positive(player.height),
+        SortField::Weight => positive(player.weight),
+        SortField::Foot => player.preferred_foot.map(|v| SortValue::Int(v as i64)),
+        SortField::FavouriteNumber => player.favourite_number.map(|v| SortValue::Int(v as i64)),
+        SortField::FavouriteTeam => text(player.favourite_team_id.and_then(club_name), locale),
+        SortField::BirthCity => text(player.birth_city.as_deref(), locale),
+        SortField::Ethnicity => Some(SortValue::Int(player.ethnicity as i64)),
+        SortField::SkinTone => Some(SortValue::Int(player.skin_tone as i64)),
+        SortField::HairColor => Some(SortValue::Int(player.hair_color as i64)),
+        SortField::RecordType => Some(SortValue::Text(collation_key(&player.record_type.to_string(), locale))),
+        SortField::PaCaGap => pa_ca_gap(player).map(|v| SortValue::Int(v as i64)),
+        SortField::Bmi => bmi(player).map(SortValue::Float),
+        SortField::Relevance => record.relevance.map(SortValue::Float),
+    }
+}
+
+/// Dense rank of every record's value for one sort key, with the direction and
+/// the placement of missing values folded in, so plain integer comparison of
+/// ranks gives the key's ordering. Equal values share a rank.
+fn column_ranks(key: &SortKey, values: &[Option<SortValue>]) -> Vec<u32> {
+    let missing = if key.nulls_first { 0 } else { u32::MAX };
+    let mut ranks = vec![missing; values.len()];
+
+    // Older first means earlier birth date first
+    let descending = if key.field == SortField::Age { !key.descending } else { key.descending };
+    let compare = |a: usize, b: usize| match (&values[a], &values[b]) {
+        (Some(a), Some(b)) => a.compare(b),
+        _ => Ordering::Equal,
+    };
+    // Rows with a value, ordered by it; the values themselves are not copied
+    let mut present: Vec<usize> = (0..values.len()).filter(|&row| values[row].is_some()).collect();
+    present.sort_by(|&a, &b| {
+        let ordering = compare(a, b);
+        if descending { ordering.reverse() } else { ordering }
+    });
+
+    let mut rank = 0;
+    for (j, &row) in present.iter().enumerate() {
+        if j == 0 || compare(present[j - 1], row) != Ordering::Equal {
+            rank += 1;
+        }
+        ranks[row] = rank;
+    }
+    ranks
+}
+
+/// Sort records by the given criteria (see `SortKey::parse`), comparing text
+/// in the alphabet of `locale`. Unknown criteria are rejected instead of being ignored.
+///
+/// Every record gets a compact key tuple of one rank per criterion, computed
+/// once up front, so the sort itself only compares small integer slices.
+/// The sort is stable: records equal on every criterion keep their input order.
+pub fn sort_players(
+    players: Vec<PlayerRecord>,
+    sort_criteria: &[String],
+    locale: CollationLocale,
+) -> Result<Vec<PlayerRecord>, String> {
+    println!("Sorting players by: {:?} ({:?})", sort_criteria, locale);
+    let keys = sort_criteria
+        .iter()
+        .map(|criterion| SortKey::parse(criterion))
+        .collect::<Result<Vec<SortKey>, String>>()?;
+
+    // Row-major table of ranks: `width` ranks per record
+    let width = keys.len();
+    let mut tuples = vec![0u32; players.len() * width];
+    for (column, key) in keys.iter().enumerate() {
+        let values: Vec<Option<SortValue>> = players.iter().map(|record| sort_value(key.field, record, locale)).collect();
+        for (row, rank) in column_ranks(key, &values).into_iter().enumerate() {
+            tuples[row * width + column] = rank;
+        }
+    }
+
+    let mut order: Vec<usize> = (0..players.len()).collect();
+    order.sort_by(|&a, &b| tuples[a * width..(a + 1) * width].cmp(&tuples[b * width..(b + 1) * width]));
+
+    let mut records: Vec<Option<PlayerRecord>> = players.into_iter().map(Some).collect();
+    let sorted: Vec<PlayerRecord> = order.into_iter().filter_map(|row| records[row].take()).collect();
+
+    println!("Sorted {} players", sorted.len());
+    Ok(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Date;
+    use crate::test_support::player;
+
+    fn record(id: usize, last_name: &str, ca: Option<i32>, birth_year: i32) -> PlayerRecord {
+        let mut player = player("Jan", last_name);
+        player.ca = ca;
+        player.birth_date = Date::new(1, 1, birth_year).unwrap();
+        PlayerRecord { id, player, relevance: None }
+    }
+
+    fn sorted_ids(records: Vec<PlayerRecord>, criteria: &[&str]) -> Vec<usize> {
+        let criteria: Vec<String> = criteria.iter().map(|c| c.to_string()).collect();
+        sort_players(records, &criteria, CollationLocale::Root)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.id)
+            .collect()
+    }
+
+    fn sample() -> Vec<PlayerRecord> {
+        vec![
+            record(0, "Nowak", Some(120), 2006),
+            record(1, "Adamski", None, 2008),
+            record(2, "Łukasik", Some(140), 2007),
+            record(3, "Zieliński", Some(120), 2005),
+        ]
+    }
+
+    #[test]
+    fn parse_sort_keys() {
+        let key = SortKey::parse("pa_ca_gap_desc_nulls_first").unwrap();
+        assert_eq!((key.field, key.descending, key.nulls_first), (SortField::PaCaGap, true, true));
+        assert!(SortKey::parse("relevance").unwrap().descending);
+        assert!(SortKey::parse("ca").is_err());
+        assert!(SortKey::parse("shoe_size_asc").is_err());
+    }
+
+    #[test]
+    fn missing_values_go_last_unless_asked() {
+        assert_eq!(sorted_ids(sample(), &["ca_desc"]), [2, 0, 3, 1]);
+        assert_eq!(sorted_ids(sample(), &["ca_asc_nulls_first"]), [1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn ties_fall_through_then_keep_input_order() {
+        assert_eq!(sorted_ids(sample(), &["ca_desc", "age_desc"]), [2, 3, 0, 1]);
+        let same = vec![record(5, "A", Some(1), 2000), record(4, "A", Some(1), 2000)];
+        assert_eq!(sorted_ids(same, &["ca_desc"]), [5, 4]);
+    }
+
+    #[test]
+    fn age_desc_is_oldest_first() {
+        assert_eq!(sorted_ids(sample(), &["age_desc"]), [3, 0, 2, 1]);
+    }
+
+    #[test]
+    fn text_keys_follow_the_locale() {
+        let names = || vec![record(0, "Łukasik", None, 2000), record(1, "Lwowski", None, 2000)];
+        let criteria = vec!["last_name_asc".to_string()];
+        let ids = |locale| -> Vec<usize> {
+            sort_players(names(), &criteria, locale).unwrap().into_iter().map(|record| record.id).collect()
+        };
+        // Root sorts Ł as L, Polish after every L
+        assert_eq!(ids(CollationLocale::Root), [0, 1]);
+        assert_eq!(ids(CollationLocale::Polish), [1, 0]);
+    }
+}
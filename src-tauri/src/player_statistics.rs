@@ -1,111 +1,29 @@
-use crate::model::{PlayerFilters, PlayerRecord, PlayerStatistics, NumberStats, TopPlayers};
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{
+    Aggregate, AggregateFunction, GroupDimension, GroupStatsCell, GroupStatsTable,
+    Histogram, HistogramBin, HistogramSeries, NumericField, Player, PlayerFilters,
+    PlayerRecord, PlayerStatistics, NumberStats, RegressionLine, RelationshipStats,
+    ScatterCell, TopPlayers, Leaderboard, LeaderboardEntry, LeaderboardGroup, SortDirection,
+};
 use crate::{get_players};
-use crate::utils::{get_birth_year, get_birth_month, matches_search_query, is_birth_date_in_range};
+use crate::player_dimensions::{GroupValue, DEFAULT_BAND_WIDTH};
+use crate::player_filters::filter_players;
+use crate::filter_presets::resolve_filters;
+use std::cmp::Ordering;
 
 #[tauri::command]
-pub fn get_player_statistics(filters: Option<PlayerFilters>) -> PlayerStatistics {
+pub fn get_player_statistics(filters: Option<PlayerFilters>) -> Result<PlayerStatistics, String> {
     println!("Getting player statistics with filters");
+    let filters = resolve_filters(filters)?;
     
     let players = get_players().lock().unwrap();
-    let filtered_players: Vec<PlayerRecord> = players
-        .iter()
-        .filter(|(id, player)| {
-            if let Some(ref f) = filters {
-                // Player IDs filter
-                if let Some(ref ids) = f.player_ids {
-                    if !ids.contains(id) {
-                        return false;
-                    }
-                }
-
-                // Name filter
-                if let Some(ref query) = f.name_query {
-                    if !matches_search_query(player, query) {
-                        return false;
-                    }
-                }
-
-                // Apply same filtering logic as get_players_chunk
-                if let Some(c) = f.country {
-                    if player.nationality_id != c {
-                        return false;
-                    }
-                }
-                if let Some(cid) = f.club {
-                    if player.club_id != Some(cid) {
-                        return false;
-                    }
-                }
-                if let Some(min_ca) = f.min_ca {
-                    if let Some(ca) = player.ca {
-                        if ca < min_ca { return false; }
-                    } else { return false; }
-                }
-                if let Some(max_ca) = f.max_ca {
-                    if let Some(ca) = player.ca {
-                        if ca > max_ca { return false; }
-                    } else { return false; }
-                }
-                if let Some(min_pa) = f.min_pa {
-                    if let Some(pa) = player.pa {
-                        if pa < min_pa { return false; }
-                    } else { return false; }
-                }
-                if let Some(max_pa) = f.max_pa {
-                    if let Some(pa) = player.pa {
-                        if pa > max_pa { return false; }
-                    } else { return false; }
-                }
-                if let Some(foot) = f.preferred_foot {
-                    if player.preferred_foot != Some(foot) {
-                        return false;
-                    }
-                }
-                if let Some(fav_num) = f.favourite_number {
-                    if player.favourite_number != Some(fav_num) {
-                        return false;
-                    }
-                }
-                if let Some(ref pos) = f.position {
-                    if player.position.as_ref() != Some(pos) {
-                        return false;
-                    }
-                }
-                if let Some(fav_club) = f.favourite_club {
-                    if player.favourite_team_id != Some(fav_club) {
-                        return false;
-                    }
-                }
-                if let Some(birth_year) = f.birth_year_min {
-                    if let Some(player_birth_year) = get_birth_year(&player.birth_date) {
-                        if player_birth_year != birth_year {
-                            return false;
-                        }
-                    } else { return false; }
-                }
-                
-                // Birth date range filter (day/month within a year)
-                if f.birth_day_from.is_some() || f.birth_month_from.is_some() || 
-                   f.birth_day_to.is_some() || f.birth_month_to.is_some() {
-                    if !is_birth_date_in_range(
-                        &player.birth_date,
-                        f.birth_day_from,
-                        f.birth_month_from,
-                        f.birth_day_to,
-                        f.birth_month_to,
-                    ) {
-                        return false;
-                    }
-                }
-            }
-            true
-        })
-        .map(|(id, player)| PlayerRecord {
-            id: *id,
-            player: player.clone(),
-        })
-        .collect();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+    Ok(statistics_of(&filtered_players))
+}
 
+/// Summary statistics of an already filtered selection
+pub(crate) fn statistics_of(filtered_players: &[PlayerRecord]) -> PlayerStatistics {
     let count = filtered_players.len();
     
     let ca_values: Vec<i32> = filtered_players.iter()
@@ -148,55 +66,51 @@ pub fn get_player_statistics(filters: Option<PlayerFilters>) -> PlayerStatistics
     };
 
     let mut position_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         if let Some(ref position) = record.player.position {
             *position_counts.entry(position.clone()).or_insert(0) += 1;
         }
     }
 
     let mut preferred_foot_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         if let Some(foot) = record.player.preferred_foot {
             *preferred_foot_counts.entry(foot).or_insert(0) += 1;
         }
     }
 
     let mut nationality_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         *nationality_counts.entry(record.player.nationality_id).or_insert(0) += 1;
     }
 
     let mut ethnicity_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         *ethnicity_counts.entry(record.player.ethnicity).or_insert(0) += 1;
     }
 
     let mut skin_tone_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         *skin_tone_counts.entry(record.player.skin_tone).or_insert(0) += 1;
     }
 
     let mut hair_color_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         *hair_color_counts.entry(record.player.hair_color).or_insert(0) += 1;
     }
 
     let mut birth_year_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
-        if let Some(year) = get_birth_year(&record.player.birth_date) {
-            *birth_year_counts.entry(year).or_insert(0) += 1;
-        }
+    for record in filtered_players {
+        *birth_year_counts.entry(record.player.birth_date.year()).or_insert(0) += 1;
     }
 
     let mut birth_month_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
-        if let Some(month) = get_birth_month(&record.player.birth_date) {
-            *birth_month_counts.entry(month).or_insert(0) += 1;
-        }
+    for record in filtered_players {
+        *birth_month_counts.entry(record.player.birth_date.month()).or_insert(0) += 1;
     }
 
     let mut club_counts = std::collections::HashMap::new();
-    for record in &filtered_players {
+    for record in filtered_players {
         if let Some(club_id) = record.player.club_id {
             *club_counts.entry(club_id).or_insert(0) += 1;
         }
@@ -221,112 +135,40 @@ pub fn get_player_statistics(filters: Option<PlayerFilters>) -> PlayerStatistics
 }
 
 #[tauri::command]
-pub fn get_top_players(filters: Option<PlayerFilters>, limit: usize) -> TopPlayers {
+pub fn get_top_players(filters: Option<PlayerFilters>, limit: usize) -> Result<TopPlayers, String> {
     println!("Getting top players with limit: {}", limit);
+    let filters = resolve_filters(filters)?;
     
     let players = get_players().lock().unwrap();
-    let filtered_players: Vec<PlayerRecord> = players
-        .iter()
-        .filter(|(id, player)| {
-            if let Some(ref f) = filters {
-                // Player IDs filter
-                if let Some(ref ids) = f.player_ids {
-                    if !ids.contains(id) {
-                        return false;
-                    }
-                }
-
-                // Name filter
-                if let Some(ref query) = f.name_query {
-                    if !matches_search_query(player, query) {
-                        return false;
-                    }
-                }
-
-                // Apply same filtering logic
-                if let Some(c) = f.country {
-                    if player.nationality_id != c { return false; }
-                }
-                if let Some(cid) = f.club {
-                    if player.club_id != Some(cid) { return false; }
-                }
-                if let Some(min_ca) = f.min_ca {
-                    if let Some(ca) = player.ca {
-                        if ca < min_ca { return false; }
-                    } else { return false; }
-                }
-                if let Some(max_ca) = f.max_ca {
-                    if let Some(ca) = player.ca {
-                        if ca > max_ca { return false; }
-                    } else { return false; }
-                }
-                if let Some(min_pa) = f.min_pa {
-                    if let Some(pa) = player.pa {
-                        if pa < min_pa { return false; }
-                    } else { return false; }
-                }
-                if let Some(max_pa) = f.max_pa {
-                    if let Some(pa) = player.pa {
-                        if pa > max_pa { return false; }
-                    } else { return false; }
-                }
-                if let Some(foot) = f.preferred_foot {
-                    if player.preferred_foot != Some(foot) { return false; }
-                }
-                if let Some(fav_num) = f.favourite_number {
-                    if player.favourite_number != Some(fav_num) { return false; }
-                }
-                if let Some(birth_year) = f.birth_year_min {
-                    if let Some(player_birth_year) = get_birth_year(&player.birth_date) {
-                        if player_birth_year != birth_year { return false; }
-                    } else { return false; }
-                }
-                
-                // Birth date range filter (day/month within a year)
-                if f.birth_day_from.is_some() || f.birth_month_from.is_some() || 
-                   f.birth_day_to.is_some() || f.birth_month_to.is_some() {
-                    if !is_birth_date_in_range(
-                        &player.birth_date,
-                        f.birth_day_from,
-                        f.birth_month_from,
-                        f.birth_day_to,
-                        f.birth_month_to,
-                    ) {
-                        return false;
-                    }
-                }
-            }
-            true
-        })
-        .map(|(id, player)| PlayerRecord {
-            id: *id,
-            player: player.clone(),
-        })
-        .collect();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+    Ok(top_players_of(&filtered_players, limit))
+}
 
+/// Top `limit` players of an already filtered selection by CA, PA, height and weight
+pub(crate) fn top_players_of(filtered_players: &[PlayerRecord], limit: usize) -> TopPlayers {
     let mut ca_players: Vec<PlayerRecord> = filtered_players.iter()
         .filter(|record| record.player.ca.is_some())
         .cloned()
         .collect();
-    ca_players.sort_by(|a, b| b.player.ca.unwrap().cmp(&a.player.ca.unwrap()));
+    ca_players.sort_by_key(|record| std::cmp::Reverse(record.player.ca));
     
     let mut pa_players: Vec<PlayerRecord> = filtered_players.iter()
         .filter(|record| record.player.pa.is_some())
         .cloned()
         .collect();
-    pa_players.sort_by(|a, b| b.player.pa.unwrap().cmp(&a.player.pa.unwrap()));
+    pa_players.sort_by_key(|record| std::cmp::Reverse(record.player.pa));
     
     let mut height_players: Vec<PlayerRecord> = filtered_players.iter()
         .filter(|record| record.player.height > 0)
         .cloned()
         .collect();
-    height_players.sort_by(|a, b| b.player.height.cmp(&a.player.height));
+    height_players.sort_by_key(|record| std::cmp::Reverse(record.player.height));
     
     let mut weight_players: Vec<PlayerRecord> = filtered_players.iter()
         .filter(|record| record.player.weight > 0)
         .cloned()
         .collect();
-    weight_players.sort_by(|a, b| b.player.weight.cmp(&a.player.weight));
+    weight_players.sort_by_key(|record| std::cmp::Reverse(record.player.weight));
 
     TopPlayers {
         top_ca: ca_players.iter().take(limit).cloned().collect(),
@@ -338,6 +180,483 @@ pub fn get_top_players(filters: Option<PlayerFilters>, limit: usize) -> TopPlaye
     }
 }
 
+/// Aggregate a filtered selection over one or two group-by dimensions.
+/// The result is a pivot table: first dimension as rows, second as columns.
+#[tauri::command]
+pub fn group_stats(
+    filters: Option<PlayerFilters>,
+    group_by: Vec<GroupDimension>,
+    aggregates: Vec<Aggregate>,
+    band_width: Option<i32>,
+) -> Result<GroupStatsTable, String> {
+    println!("Grouping players by {:?}", group_by);
+    if group_by.is_empty() || group_by.len() > 2 {
+        return Err("group_stats needs one or two group-by dimensions".to_string());
+    }
+    let band_width = band_width.unwrap_or(DEFAULT_BAND_WIDTH);
+    if band_width <= 0 {
+        return Err(format!("Band width must be positive, got {}", band_width));
+    }
+    for aggregate in &aggregates {
+        if aggregate.field.is_none() && aggregate.function != AggregateFunction::Count {
+            return Err(format!("Aggregate {} needs a field", aggregate.function.name()));
+        }
+    }
+    let filters = resolve_filters(filters)?;
+
+    let players = get_players().lock().unwrap();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+    Ok(group_stats_of(&filtered_players, &group_by, &aggregates, band_width))
+}
+
+/// Pivot table of an already filtered selection; `group_by` holds one or two
+/// dimensions and every non-count aggregate has a field
+pub(crate) fn group_stats_of(
+    filtered_players: &[PlayerRecord],
+    group_by: &[GroupDimension],
+    aggregates: &[Aggregate],
+    band_width: i32,
+) -> GroupStatsTable {
+    let row_dimension = group_by[0];
+    let column_dimension = group_by.get(1).copied();
+    let mut groups: HashMap<(GroupValue, Option<GroupValue>), Vec<&Player>> = HashMap::new();
+    for record in filtered_players {
+        let row = row_dimension.value(&record.player, band_width);
+        let column = column_dimension.map(|dimension| dimension.value(&record.player, band_width));
+        groups.entry((row, column)).or_default().push(&record.player);
+    }
+
+    // Distinct row and column values in display order
+    let mut row_values: Vec<GroupValue> = groups.keys()
+        .map(|(row, _)| row.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    row_values.sort_by(|a, b| row_dimension.compare(a, b, band_width));
+    let mut column_values: Vec<GroupValue> = groups.keys()
+        .filter_map(|(_, column)| column.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if let Some(dimension) = column_dimension {
+        column_values.sort_by(|a, b| dimension.compare(a, b, band_width));
+    }
+    let row_index: HashMap<&GroupValue, usize> = row_values.iter().enumerate().map(|(i, v)| (v, i)).collect();
+    let column_index: HashMap<&GroupValue, usize> = column_values.iter().enumerate().map(|(i, v)| (v, i)).collect();
+
+    let mut cells: Vec<GroupStatsCell> = groups
+        .iter()
+        .map(|((row, column), members)| GroupStatsCell {
+            row: row_index[row],
+            column: column.as_ref().map(|column| column_index[column]),
+            count: members.len(),
+            values: aggregates
+                .iter()
+                .map(|aggregate| match aggregate.field {
+                    Some(field) => {
+                        let mut values: Vec<f64> = members.iter().filter_map(|p| field.value(p)).collect();
+                        aggregate.function.apply(&mut values)
+                    }
+                    None => Some(members.len() as f64),
+                })
+                .collect(),
+        })
+        .collect();
+    cells.sort_by_key(|cell| (cell.row, cell.column));
+
+    GroupStatsTable {
+        group_by: group_by.to_vec(),
+        aggregates: aggregates
+            .iter()
+            .map(|aggregate| match aggregate.field {
+                Some(field) => format!("{}_{}", aggregate.function.name(), field.name()),
+                None => aggregate.function.name().to_string(),
+            })
+            .collect(),
+        rows: row_values.iter().map(|v| row_dimension.key(v, band_width)).collect(),
+        columns: match column_dimension {
+            Some(dimension) => column_values.iter().map(|v| dimension.key(v, band_width)).collect(),
+            None => Vec::new(),
+        },
+        cells,
+    }
+}
+
+/// A player with the metric value it is ranked by
+type Scored<'a> = (f64, &'a PlayerRecord);
+
+/// Top `limit` players by any numeric metric, overall or per group.
+/// With `relative_to_age` each value is compared with the average of players
+/// born the same year in the selection (e.g. PA relative to age).
+#[tauri::command]
+pub fn get_leaderboard(
+    filters: Option<PlayerFilters>,
+    metric: NumericField,
+    direction: Option<SortDirection>,
+    limit: usize,
+    group_by: Option<GroupDimension>,
+    relative_to_age: Option<bool>,
+) -> Result<Leaderboard, String> {
+    let direction = direction.unwrap_or_default();
+    let relative_to_age = relative_to_age.unwrap_or(false);
+    println!("Building {} leaderboard ({:?}, top {}, by {:?})", metric.name(), direction, limit, group_by);
+    let filters = resolve_filters(filters)?;
+
+    let players = get_players().lock().unwrap();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+
+    let mut scored: Vec<Scored> = filtered_players
+        .iter()
+        .filter_map(|record| metric.value(&record.player).map(|value| (value, record)))
+        .collect();
+
+    if relative_to_age {
+        let mut cohorts: HashMap<i32, (f64, usize)> = HashMap::new();
+        for (value, record) in &scored {
+            let cohort = cohorts.entry(record.player.birth_date.year()).or_default();
+            cohort.0 += value;
+            cohort.1 += 1;
+        }
+        for (value, record) in &mut scored {
+            let (sum, count) = cohorts[&record.player.birth_date.year()];
+            *value -= sum / count as f64;
+        }
+    }
+
+    // Stable sort, so ties stay in ID order
+    scored.sort_by(|(a, _), (b, _)| match direction {
+        SortDirection::Desc => b.total_cmp(a),
+        SortDirection::Asc => a.total_cmp(b),
+    });
+
+    let groups: Vec<(Option<GroupValue>, Vec<Scored>)> = match group_by {
+        Some(dimension) => {
+            let mut by_value: HashMap<GroupValue, Vec<Scored>> = HashMap::new();
+            for (value, record) in scored {
+                by_value.entry(dimension.value(&record.player, DEFAULT_BAND_WIDTH)).or_default().push((value, record));
+            }
+            let mut groups: Vec<_> = by_value.into_iter().map(|(group, members)| (Some(group), members)).collect();
+            groups.sort_by(|(a, _), (b, _)| match (a, b) {
+                (Some(a), Some(b)) => dimension.compare(a, b, DEFAULT_BAND_WIDTH),
+                _ => Ordering::Equal,
+            });
+            groups
+        }
+        None => vec![(None, scored)],
+    };
+
+    Ok(Leaderboard {
+        metric,
+        direction,
+        relative_to_age,
+        groups: groups
+            .into_iter()
+            .map(|(group, members)| {
+                let mut entries: Vec<LeaderboardEntry> = Vec::with_capacity(limit.min(members.len()));
+                for (position, (value, record)) in members.into_iter().take(limit).enumerate() {
+                    let rank = match entries.last() {
+                        Some(previous) if previous.value == value => previous.rank,
+                        _ => position + 1,
+                    };
+                    entries.push(LeaderboardEntry { rank, value, record: record.clone() });
+                }
+                LeaderboardGroup {
+                    group: group.zip(group_by).map(|(value, dimension)| dimension.key(&value, DEFAULT_BAND_WIDTH)),
+                    entries,
+                }
+            })
+            .collect(),
+    })
+}
+
+/// Number of bins when the request sets neither a bin width nor a bin count
+const DEFAULT_BIN_COUNT: usize = 20;
+/// Upper bound on the number of bins, requested or implied by a bin width
+const MAX_BIN_COUNT: usize = 1000;
+
+/// Equal-width bins over `min..=max`; values outside the range are clamped
+/// into the first or last bin
+struct Binning {
+    min: f64,
+    width: f64,
+    count: usize,
+}
+
+impl Binning {
+    fn new(min: f64, max: f64, bin_width: Option<f64>, bin_count: Option<usize>) -> Result<Binning, String> {
+        match (bin_width, bin_count) {
+            (Some(_), Some(_)) => Err("Set either a bin width or a bin count, not both".to_string()),
+            (Some(width), None) => {
+                if width <= 0.0 || !width.is_finite() {
+                    return Err(format!("Bin width must be positive, got {}", width));
+                }
+                // Checked as a float first, so a tiny width can't overflow the count
+                let bins = ((max - min) / width).floor() + 1.0;
+                if bins > MAX_BIN_COUNT as f64 {
+                    return Err(format!("Bin width {} gives more than {} bins", width, MAX_BIN_COUNT));
+                }
+                Ok(Binning { min, width, count: bins as usize })
+            }
+            (None, count) => {
+                let count = count.unwrap_or(DEFAULT_BIN_COUNT);
+                if count == 0 || count > MAX_BIN_COUNT {
+                    return Err(format!("Bin count must be between 1 and {}, got {}", MAX_BIN_COUNT, count));
+                }
+                // A single distinct value still gets a bin of width 1
+                let width = if max > min { (max - min) / count as f64 } else { 1.0 };
+                Ok(Binning { min, width, count })
+            }
+        }
+    }
+
+    fn index(&self, value: f64) -> usize {
+        let index = ((value - self.min) / self.width).floor();
+        (index.max(0.0) as usize).min(self.count - 1)
+    }
+
+    fn empty_bins(&self) -> Vec<HistogramBin> {
+        (0..self.count)
+            .map(|i| HistogramBin {
+                start: self.min + i as f64 * self.width,
+                end: self.min + (i + 1) as f64 * self.width,
+                count: 0,
+                player_ids: Vec::new(),
+            })
+            .collect()
+    }
+}
+
+/// Histogram of a numeric field over a filtered selection, optionally split by
+/// a dimension. Each bin lists its player IDs.
+#[tauri::command]
+pub fn histogram(
+    filters: Option<PlayerFilters>,
+    field: NumericField,
+    bin_width: Option<f64>,
+    bin_count: Option<usize>,
+    min: Option<f64>,
+    max: Option<f64>,
+    split_by: Option<GroupDimension>,
+) -> Result<Histogram, String> {
+    println!("Building histogram of {} (split by {:?})", field.name(), split_by);
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(format!("Histogram min {} is greater than max {}", min, max));
+        }
+    }
+    let filters = resolve_filters(filters)?;
+
+    let players = get_players().lock().unwrap();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+    histogram_of(&filtered_players, field, bin_width, bin_count, min, max, split_by)
+}
+
+/// Histogram of an already filtered selection; `min` is not above `max`
+pub(crate) fn histogram_of(
+    filtered_players: &[PlayerRecord],
+    field: NumericField,
+    bin_width: Option<f64>,
+    bin_count: Option<usize>,
+    min: Option<f64>,
+    max: Option<f64>,
+    split_by: Option<GroupDimension>,
+) -> Result<Histogram, String> {
+    let values: Vec<(usize, f64, &Player)> = filtered_players
+        .iter()
+        .filter_map(|record| field.value(&record.player).map(|value| (record.id, value, &record.player)))
+        .collect();
+    let missing = filtered_players.len() - values.len();
+
+    let data_min = values.iter().map(|(_, v, _)| *v).fold(f64::INFINITY, f64::min);
+    let data_max = values.iter().map(|(_, v, _)| *v).fold(f64::NEG_INFINITY, f64::max);
+    let min = min.unwrap_or(if values.is_empty() { 0.0 } else { data_min });
+    let max = max.unwrap_or(if values.is_empty() { min } else { data_max }).max(min);
+    let binning = Binning::new(min, max, bin_width, bin_count)?;
+
+    let mut bins = binning.empty_bins();
+    let mut split: HashMap<GroupValue, Vec<HistogramBin>> = HashMap::new();
+    for (id, value, player) in &values {
+        let index = binning.index(*value);
+        bins[index].count += 1;
+        bins[index].player_ids.push(*id);
+        if let Some(dimension) = split_by {
+            let group = split
+                .entry(dimension.value(player, DEFAULT_BAND_WIDTH))
+                .or_insert_with(|| binning.empty_bins());
+            group[index].count += 1;
+            group[index].player_ids.push(*id);
+        }
+    }
+
+    let mut series: Vec<(GroupValue, Vec<HistogramBin>)> = split.into_iter().collect();
+    let series = match split_by {
+        Some(dimension) => {
+            series.sort_by(|(a, _), (b, _)| dimension.compare(a, b, DEFAULT_BAND_WIDTH));
+            series
+                .into_iter()
+                .map(|(value, bins)| HistogramSeries {
+                    group: dimension.key(&value, DEFAULT_BAND_WIDTH),
+                    bins,
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    Ok(Histogram {
+        field,
+        min,
+        max,
+        bin_width: binning.width,
+        bins,
+        series,
+        missing,
+    })
+}
+
+/// Grid size per axis of the scatter data when the request doesn't set one
+const DEFAULT_SCATTER_GRID: usize = 20;
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Pearson correlation, `None` for fewer than two points or a constant series
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    if xs.len() < 2 {
+        return None;
+    }
+    let (mean_x, mean_y) = (mean(xs), mean(ys));
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (x, y) in xs.iter().zip(ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+    Some(covariance / (variance_x * variance_y).sqrt())
+}
+
+/// 1-based ranks; tied values share the average of their ranks
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        for &i in &order[start..=end] {
+            ranks[i] = rank;
+        }
+        start = end + 1;
+    }
+    ranks
+}
+
+/// Least-squares line, `None` for fewer than two points or a constant x.
+/// A constant y gives a flat line through its mean.
+fn regression(xs: &[f64], ys: &[f64]) -> Option<RegressionLine> {
+    if xs.len() < 2 {
+        return None;
+    }
+    let (mean_x, mean_y) = (mean(xs), mean(ys));
+    let covariance: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance_x: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if variance_x == 0.0 {
+        return None;
+    }
+    let slope = covariance / variance_x;
+    Some(RegressionLine {
+        slope,
+        intercept: mean_y - slope * mean_x,
+        r_squared: pearson(xs, ys).map(|r| r * r),
+    })
+}
+
+/// Correlation, regression line and binned scatter data for two numeric fields
+/// over a filtered selection (e.g. height/weight, CA/PA, birth year/PA)
+#[tauri::command]
+pub fn relationship_stats(
+    filters: Option<PlayerFilters>,
+    x: NumericField,
+    y: NumericField,
+    grid_size: Option<usize>,
+) -> Result<RelationshipStats, String> {
+    println!("Relating {} to {}", x.name(), y.name());
+    let grid_size = grid_size.unwrap_or(DEFAULT_SCATTER_GRID);
+    let filters = resolve_filters(filters)?;
+
+    let players = get_players().lock().unwrap();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+    relationship_stats_of(&filtered_players, x, y, grid_size)
+}
+
+/// Correlation, regression and scatter grid of an already filtered selection
+pub(crate) fn relationship_stats_of(
+    filtered_players: &[PlayerRecord],
+    x: NumericField,
+    y: NumericField,
+    grid_size: usize,
+) -> Result<RelationshipStats, String> {
+    let (xs, ys): (Vec<f64>, Vec<f64>) = filtered_players
+        .iter()
+        .filter_map(|record| Some((x.value(&record.player)?, y.value(&record.player)?)))
+        .unzip();
+
+    let scatter = if xs.is_empty() {
+        Vec::new()
+    } else {
+        let min_max = |values: &[f64]| {
+            values.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)))
+        };
+        let (min_x, max_x) = min_max(&xs);
+        let (min_y, max_y) = min_max(&ys);
+        let x_bins = Binning::new(min_x, max_x, None, Some(grid_size))?;
+        let y_bins = Binning::new(min_y, max_y, None, Some(grid_size))?;
+
+        // (count, sum x, sum y) per grid cell
+        let mut cells: HashMap<(usize, usize), (usize, f64, f64)> = HashMap::new();
+        for (&x, &y) in xs.iter().zip(&ys) {
+            let cell = cells.entry((x_bins.index(x), y_bins.index(y))).or_default();
+            cell.0 += 1;
+            cell.1 += x;
+            cell.2 += y;
+        }
+        let mut cells: Vec<_> = cells.into_iter().collect();
+        cells.sort_by_key(|(cell, _)| *cell);
+        cells
+            .into_iter()
+            .map(|((i, j), (count, sum_x, sum_y))| ScatterCell {
+                x_start: x_bins.min + i as f64 * x_bins.width,
+                x_end: x_bins.min + (i + 1) as f64 * x_bins.width,
+                y_start: y_bins.min + j as f64 * y_bins.width,
+                y_end: y_bins.min + (j + 1) as f64 * y_bins.width,
+                count,
+                mean_x: sum_x / count as f64,
+                mean_y: sum_y / count as f64,
+            })
+            .collect()
+    };
+
+    Ok(RelationshipStats {
+        x,
+        y,
+        count: xs.len(),
+        pearson: pearson(&xs, &ys),
+        spearman: pearson(&ranks(&xs), &ranks(&ys)),
+        regression: regression(&xs, &ys),
+        scatter,
+    })
+}
+
 fn calculate_number_stats(values: &[i32]) -> NumberStats {
     let mut sorted_values: Vec<f64> = values.iter().map(|&v| v as f64).collect();
     sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
@@ -345,7 +664,7 @@ fn calculate_number_stats(values: &[i32]) -> NumberStats {
     let len = sorted_values.len();
     let mean = sorted_values.iter().sum::<f64>() / len as f64;
     
-    let median = if len % 2 == 0 {
+    let median = if len.is_multiple_of(2) {
         (sorted_values[len / 2 - 1] + sorted_values[len / 2]) / 2.0
     } else {
         sorted_values[len / 2]
@@ -371,3 +690,95 @@ fn calculate_number_stats(values: &[i32]) -> NumberStats {
         std_dev,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn pearson_of_linear_series() {
+        assert!(close(pearson(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]).unwrap(), 1.0));
+        assert!(close(pearson(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]).unwrap(), -1.0));
+    }
+
+    #[test]
+    fn pearson_undefined_for_constant_or_single_point() {
+        assert_eq!(pearson(&[1.0], &[1.0]), None);
+        assert_eq!(pearson(&[1.0, 2.0, 3.0], &[5.0, 5.0, 5.0]), None);
+    }
+
+    #[test]
+    fn ranks_average_ties() {
+        assert_eq!(ranks(&[10.0, 20.0, 20.0, 5.0]), [2.0, 3.5, 3.5, 1.0]);
+    }
+
+    #[test]
+    fn spearman_sees_monotonic_curves() {
+        let xs = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys: Vec<f64> = xs.iter().map(|x: &f64| x.powi(3)).collect();
+        assert!(pearson(&xs, &ys).unwrap() < 1.0);
+        assert!(close(pearson(&ranks(&xs), &ranks(&ys)).unwrap(), 1.0));
+    }
+
+    #[test]
+    fn regression_fits_a_line() {
+        let line = regression(&[0.0, 1.0, 2.0], &[1.0, 3.0, 5.0]).unwrap();
+        assert!(close(line.slope, 2.0) && close(line.intercept, 1.0));
+        assert!(close(line.r_squared.unwrap(), 1.0));
+    }
+
+    #[test]
+    fn constant_y_gives_a_flat_line() {
+        let line = regression(&[1.0, 2.0, 3.0], &[4.0, 4.0, 4.0]).unwrap();
+        assert_eq!((line.slope, line.intercept, line.r_squared), (0.0, 4.0, None));
+    }
+
+    #[test]
+    fn constant_x_has_no_line() {
+        assert!(regression(&[2.0, 2.0, 2.0], &[1.0, 2.0, 3.0]).is_none());
+    }
+
+    #[test]
+    fn bin_width_covers_the_range() {
+        let binning = Binning::new(0.0, 199.0, Some(10.0), None).unwrap();
+        assert_eq!(binning.count, 20);
+        assert_eq!(binning.index(0.0), 0);
+        assert_eq!(binning.index(9.99), 0);
+        assert_eq!(binning.index(10.0), 1);
+        assert_eq!(binning.index(199.0), 19);
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        let binning = Binning::new(0.0, 100.0, None, Some(4)).unwrap();
+        assert_eq!(binning.index(-5.0), 0);
+        assert_eq!(binning.index(100.0), 3);
+        assert_eq!(binning.index(500.0), 3);
+    }
+
+    #[test]
+    fn constant_values_get_one_unit_bin() {
+        let binning = Binning::new(7.0, 7.0, None, Some(5)).unwrap();
+        assert_eq!(binning.width, 1.0);
+        assert_eq!(binning.index(7.0), 0);
+    }
+
+    #[test]
+    fn invalid_binning_is_rejected() {
+        assert!(Binning::new(0.0, 10.0, Some(1.0), Some(5)).is_err());
+        assert!(Binning::new(0.0, 10.0, Some(0.0), None).is_err());
+        assert!(Binning::new(0.0, 10.0, Some(f64::NAN), None).is_err());
+        assert!(Binning::new(0.0, 10.0, None, Some(0)).is_err());
+    }
+
+    #[test]
+    fn bin_count_is_capped() {
+        assert!(Binning::new(0.0, 200.0, Some(1e-9), None).is_err());
+        assert!(Binning::new(0.0, 200.0, None, Some(MAX_BIN_COUNT + 1)).is_err());
+        assert_eq!(Binning::new(0.0, 999.0, Some(1.0), None).unwrap().count, MAX_BIN_COUNT);
+    }
+}
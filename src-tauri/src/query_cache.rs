@@ -0,0 +1,104 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::model::PlayerFilters;
+
+/// Maximum number of result sets kept at once; the least recently used one is dropped first.
+/// A dropped handle is unknown from then on, and its owner has to create the query again.
+const MAX_CACHED_QUERIES: usize = 32;
+
+/// Sorted result set of one filter + sort combination
+pub struct CachedQuery {
+    pub filters: Option<PlayerFilters>,
+    pub ids: Vec<usize>,
+    /// `PLAYERS` version the IDs were computed against
+    pub version: u64,
+    last_used: u64,
+}
+
+#[derive(Default)]
+pub struct QueryCache {
+    entries: HashMap<String, CachedQuery>,
+    clock: u64,
+}
+
+/// Stable handle for a filter + sort combination.
+/// Filters are expected to be resolved already (no preset reference).
+pub fn query_handle(filters: Option<&PlayerFilters>) -> String {
+    let json = serde_json::to_string(&filters).unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    json.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+impl QueryCache {
+    /// Cached entry for `handle`, only if it was built against `version`
+    pub fn get_fresh(&mut self, handle: &str, version: u64) -> Option<&CachedQuery> {
+        self.clock += 1;
+        let clock = self.clock;
+        match self.entries.get_mut(handle) {
+            Some(entry) if entry.version == version => {
+                entry.last_used = clock;
+                Some(entry)
+            }
+            _ => None,
+        }
+    }
+
+    /// Filters of a known handle, fresh or stale
+    pub fn filters(&self, handle: &str) -> Option<Option<PlayerFilters>> {
+        self.entries.get(handle).map(|entry| entry.filters.clone())
+    }
+
+    pub fn insert(&mut self, handle: String, filters: Option<PlayerFilters>, ids: Vec<usize>, version: u64) {
+        self.clock += 1;
+        // Stale entries are kept: their filters are still needed to rebuild them
+        if self.entries.len() >= MAX_CACHED_QUERIES && !self.entries.contains_key(&handle) {
+            let oldest = self.entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(handle, CachedQuery {
+            filters,
+            ids,
+            version,
+            last_used: self.clock,
+        });
+    }
+
+    pub fn remove(&mut self, handle: &str) -> bool {
+        self.entries.remove(handle).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_query_is_evicted() {
+        let mut cache = QueryCache::default();
+        for i in 0..MAX_CACHED_QUERIES {
+            cache.insert(format!("q{}", i), None, vec![i], 1);
+        }
+        // Touching the oldest entry makes the second one the eviction candidate
+        assert!(cache.get_fresh("q0", 1).is_some());
+        cache.insert("new".to_string(), None, Vec::new(), 1);
+        assert!(cache.filters("q0").is_some());
+        assert!(cache.filters("q1").is_none());
+        assert!(cache.filters("new").is_some());
+    }
+
+    #[test]
+    fn stale_entries_keep_their_filters() {
+        let mut cache = QueryCache::default();
+        cache.insert("q".to_string(), None, vec![1], 1);
+        assert!(cache.get_fresh("q", 2).is_none());
+        assert!(cache.filters("q").is_some());
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use crate::{bump_players_version, get_player_index, get_players};
+use crate::filter_presets::resolve_filters;
+use crate::model::{Date, Player, PlayerFilters, RecordType, RegenEligibility, RegenIssue, RegenIssueKind};
+use crate::player_filters::filter_players;
+use crate::talent_pipeline::DEFAULT_INTAKE_AGE;
+
+/// Regens at or above this age on the game start date are no longer future regens
+const REGEN_AGE_LIMIT: i32 = 16;
+/// Seasons a save is expected to run when the request doesn't say
+const DEFAULT_SEASONS: i32 = 20;
+/// Longest save a check may cover
+const MAX_SEASONS: i32 = 100;
+
+/// Birth dates that make a future regen appear during the save
+struct Window {
+    start: Date,
+    first_season: i32,
+    last_season: i32,
+    intake_age: i32,
+}
+
+impl Window {
+    fn new(game_year: i32, start_date: Option<String>, seasons: Option<i32>, intake_age: Option<i32>) -> Result<Window, String> {
+        let start = match start_date {
+            Some(date) => Date::parse(&date)?,
+            None if game_year > 0 => Date::game_start(game_year),
+            None => return Err("A game year or a start date is required".to_string()),
+        };
+        let seasons = seasons.unwrap_or(DEFAULT_SEASONS);
+        if !(1..=MAX_SEASONS).contains(&seasons) {
+            return Err(format!("A save must run for 1 to {} seasons, got {}", MAX_SEASONS, seasons));
+        }
+        // Regens joining at the age limit or later would be moved to dates
+        // that are already too old at the game start
+        let intake_age = intake_age.unwrap_or(DEFAULT_INTAKE_AGE);
+        if !(1..REGEN_AGE_LIMIT).contains(&intake_age) {
+            return Err(format!("Intake age must be between 1 and {}, got {}", REGEN_AGE_LIMIT - 1, intake_age));
+        }
+        let last_season = start
+            .year()
+            .checked_add(seasons - 1)
+            .ok_or_else(|| format!("Start date {} is too far in the future", start))?;
+        Ok(Window {
+            start,
+            first_season: start.year(),
+            last_season,
+            intake_age,
+        })
+    }
+
+    /// What is wrong with a birth date, the age at game start and the number
+    /// of years the birth year has to move to fix it
+    fn check(&self, birth: Date) -> (Option<RegenIssueKind>, i32, i32) {
+        let age = birth.age_on(self.start);
+        if age >= REGEN_AGE_LIMIT {
+            return (Some(RegenIssueKind::TooOld), age, age - (REGEN_AGE_LIMIT - 1));
+        }
+        let intake_season = birth.year().saturating_add(self.intake_age);
+        if intake_season > self.last_season {
+            return (Some(RegenIssueKind::TooYoung), age, self.last_season - intake_season);
+        }
+        (None, age, 0)
+    }
+}
+
+fn collect_issues(
+    players: &BTreeMap<usize, Player>,
+    filters: Option<&PlayerFilters>,
+    window: &Window,
+) -> Result<RegenEligibility, String> {
+    let mut eligibility = RegenEligibility {
+        game_start: window.start,
+        first_season: window.first_season,
+        last_season: window.last_season,
+        checked: 0,
+        too_old: 0,
+        too_young: 0,
+        issues: Vec::new(),
+    };
+    for record in filter_players(players, filters)? {
+        if record.player.record_type != RecordType::DetailedFutureRegen {
+            continue;
+        }
+        eligibility.checked += 1;
+        let birth = record.player.birth_date;
+        let (kind, age_at_start, shift) = window.check(birth);
+        let kind = match kind {
+            Some(kind) => kind,
+            None => continue,
+        };
+        match kind {
+            RegenIssueKind::TooOld => eligibility.too_old += 1,
+            RegenIssueKind::TooYoung => eligibility.too_young += 1,
+        }
+        eligibility.issues.push(RegenIssue {
+            kind,
+            age_at_start,
+            suggested_birth_date: birth.shift_years(shift),
+            record,
+        });
+    }
+    Ok(eligibility)
+}
+
+/// Flag future regens that are too old to still be regens at the game start,
+/// or too young to reach the youth intake age within `seasons` seasons.
+/// The start date defaults to the one derived from `game_year`.
+#[tauri::command]
+pub fn check_regen_eligibility(
+    filters: Option<PlayerFilters>,
+    game_year: i32,
+    start_date: Option<String>,
+    seasons: Option<i32>,
+    intake_age: Option<i32>,
+) -> Result<RegenEligibility, String> {
+    let window = Window::new(game_year, start_date, seasons, intake_age)?;
+    let filters = resolve_filters(filters)?;
+    let players = get_players().lock().map_err(|e| e.to_string())?;
+    let eligibility = collect_issues(&players, filters.as_ref(), &window)?;
+    println!(
+        "[REGEN] Checked {} regens: {} too old, {} too young",
+        eligibility.checked, eligibility.too_old, eligibility.too_young
+    );
+    Ok(eligibility)
+}
+
+/// Shift the birth year of every regen `check_regen_eligibility` flags by the
+/// fewest years that bring it into the window. Returns how many were changed.
+#[tauri::command]
+pub fn fix_regen_eligibility(
+    filters: Option<PlayerFilters>,
+    game_year: i32,
+    start_date: Option<String>,
+    seasons: Option<i32>,
+    intake_age: Option<i32>,
+) -> Result<usize, String> {
+    let window = Window::new(game_year, start_date, seasons, intake_age)?;
+    let filters = resolve_filters(filters)?;
+    let mut players = get_players().lock().map_err(|e| e.to_string())?;
+    let issues = collect_issues(&players, filters.as_ref(), &window)?.issues;
+    if issues.is_empty() {
+        return Ok(0);
+    }
+
+    let mut index = get_player_index().lock().map_err(|e| e.to_string())?;
+    for issue in &issues {
+        let id = issue.record.id;
+        if let Some(player) = players.get_mut(&id) {
+            index.remove(id, player);
+            player.birth_date = issue.suggested_birth_date;
+            index.insert(id, player);
+        }
+    }
+    bump_players_version();
+    println!("[REGEN] Shifted the birth year of {} regens", issues.len());
+    Ok(issues.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32, month: u32, year: i32) -> Date {
+        Date::new(day, month, year).unwrap()
+    }
+
+    /// Save starting 01/07/2023 and running until the 2027 season, intake at 15
+    fn window() -> Window {
+        Window::new(2024, None, Some(5), Some(15)).unwrap()
+    }
+
+    #[test]
+    fn window_defaults_to_the_game_start() {
+        let window = window();
+        assert_eq!(window.start, date(1, 7, 2023));
+        assert_eq!((window.first_season, window.last_season), (2023, 2027));
+        assert!(Window::new(2024, Some("31/02/2023".to_string()), None, None).is_err());
+        assert!(Window::new(0, None, None, None).is_err());
+    }
+
+    #[test]
+    fn out_of_range_parameters_are_rejected() {
+        assert!(Window::new(2024, None, Some(0), None).is_err());
+        assert!(Window::new(2024, None, Some(MAX_SEASONS + 1), None).is_err());
+        assert!(Window::new(2024, None, None, Some(0)).is_err());
+        assert!(Window::new(2024, None, None, Some(REGEN_AGE_LIMIT)).is_err());
+        assert!(Window::new(2024, None, None, Some(REGEN_AGE_LIMIT - 1)).is_ok());
+    }
+
+    #[test]
+    fn fixed_dates_pass_the_check() {
+        for intake_age in 1..REGEN_AGE_LIMIT {
+            for seasons in [1, 5, DEFAULT_SEASONS] {
+                let window = Window::new(2024, Some("28/02/2024".to_string()), Some(seasons), Some(intake_age)).unwrap();
+                let births = (1990..2040).flat_map(|year| {
+                    [(1, 1), (28, 2), (29, 2), (1, 3), (1, 7), (31, 12)]
+                        .into_iter()
+                        .filter_map(move |(day, month)| Date::new(day, month, year))
+                });
+                for birth in births {
+                    let (kind, _, shift) = window.check(birth);
+                    if kind.is_some() {
+                        let fixed = birth.shift_years(shift);
+                        assert_eq!(window.check(fixed).0, None, "{} moved to {}", birth, fixed);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn too_old_regens_move_forward_just_enough() {
+        let window = window();
+        let (kind, age, shift) = window.check(date(1, 7, 2007));
+        assert_eq!((kind, age, shift), (Some(RegenIssueKind::TooOld), 16, 1));
+        assert_eq!(window.check(date(1, 7, 2007).shift_years(shift)).0, None);
+        assert_eq!(window.check(date(2, 7, 2007)).0, None);
+    }
+
+    #[test]
+    fn too_young_regens_move_back_just_enough() {
+        let window = window();
+        let (kind, _, shift) = window.check(date(1, 1, 2015));
+        assert_eq!((kind, shift), (Some(RegenIssueKind::TooYoung), -3));
+        assert_eq!(window.check(date(1, 1, 2012)).0, None);
+    }
+
+    #[test]
+    fn leap_day_births_shift_to_28_february() {
+        let window = Window::new(2024, Some("01/03/2024".to_string()), Some(5), Some(15)).unwrap();
+        let birth = date(29, 2, 2008);
+        let (kind, _, shift) = window.check(birth);
+        assert_eq!(kind, Some(RegenIssueKind::TooOld));
+        let fixed = birth.shift_years(shift);
+        assert_eq!(fixed, date(28, 2, 2009));
+        assert_eq!(window.check(fixed).0, None);
+    }
+}
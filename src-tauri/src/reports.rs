@@ -0,0 +1,307 @@
+use std::fs;
+
+use crate::get_players;
+use crate::model::{
+    Aggregate, AggregateFunction, GroupDimension, NumberStats, NumericField, PlayerFilters, PlayerRecord,
+    ReportFormat,
+};
+use crate::filter_presets::resolve_filters;
+use crate::player_dimensions::DEFAULT_BAND_WIDTH;
+use crate::player_filters::filter_players;
+use crate::player_statistics::{group_stats_of, statistics_of, top_players_of};
+use crate::utils::{club_name, country_name, escape_html, game_age, get_position_short};
+
+/// Players listed per top-players table when the request doesn't set a limit
+const DEFAULT_TOP_LIMIT: usize = 10;
+/// Nations listed in the nationality table
+const NATIONALITY_ROWS: usize = 20;
+
+const REPORT_TITLE: &str = "Player database report";
+
+const DEFAULT_MARKDOWN_TEMPLATE: &str = "# {{title}}
+
+Filters: {{filters}}
+
+## Summary
+
+Players: {{count}}
+
+{{summary}}
+
+## Top players
+
+{{top_players}}
+
+## Nationalities
+
+{{nationalities}}
+
+## Breakdowns
+
+{{groups}}
+";
+
+const DEFAULT_HTML_TEMPLATE: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{{title}}</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+th { background: #f0f0f0; }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<p>Filters: {{filters}}</p>
+<h2>Summary</h2>
+<p>Players: {{count}}</p>
+{{summary}}
+<h2>Top players</h2>
+{{top_players}}
+<h2>Nationalities</h2>
+{{nationalities}}
+<h2>Breakdowns</h2>
+{{groups}}
+</body>
+</html>
+";
+
+/// A report table, rendered to either format
+struct Table {
+    caption: Option<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new(caption: Option<String>, headers: &[&str]) -> Table {
+        Table {
+            caption,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => {
+                let row = |cells: &[String]| {
+                    let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+                    format!("| {} |\n", cells.join(" | "))
+                };
+                let mut out = String::new();
+                if let Some(ref caption) = self.caption {
+                    out.push_str(&format!("### {}\n\n", caption));
+                }
+                if self.rows.is_empty() {
+                    out.push_str("_No players._\n");
+                    return out;
+                }
+                out.push_str(&row(&self.headers));
+                out.push_str(&format!("|{}\n", " --- |".repeat(self.headers.len())));
+                for cells in &self.rows {
+                    out.push_str(&row(cells));
+                }
+                out
+            }
+            ReportFormat::Html => {
+                let mut out = String::new();
+                if let Some(ref caption) = self.caption {
+                    out.push_str(&format!("<h3>{}</h3>\n", escape_html(caption)));
+                }
+                if self.rows.is_empty() {
+                    out.push_str("<p><em>No players.</em></p>\n");
+                    return out;
+                }
+                out.push_str("<table>\n<tr>");
+                for header in &self.headers {
+                    out.push_str(&format!("<th>{}</th>", escape_html(header)));
+                }
+                out.push_str("</tr>\n");
+                for cells in &self.rows {
+                    out.push_str("<tr>");
+                    for cell in cells {
+                        out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+                out
+            }
+        }
+    }
+}
+
+fn number(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.fract() == 0.0 => format!("{}", value),
+        Some(value) => format!("{:.1}", value),
+        None => "-".to_string(),
+    }
+}
+
+fn stats_row(label: &str, stats: &Option<NumberStats>) -> Vec<String> {
+    let mut row = vec![label.to_string()];
+    match stats {
+        Some(stats) => row.extend([stats.min, stats.mean, stats.median, stats.max].map(|v| number(Some(v)))),
+        None => row.extend(std::iter::repeat_n("-".to_string(), 4)),
+    }
+    row
+}
+
+fn display_name(record: &PlayerRecord) -> String {
+    let player = &record.player;
+    match player.common_name.as_deref() {
+        Some(common) if !common.is_empty() => common.to_string(),
+        _ => format!("{} {}", player.first_name, player.last_name),
+    }
+}
+
+fn top_table(caption: &str, records: &[PlayerRecord]) -> Table {
+    let mut table = Table::new(Some(caption.to_string()), &["#", "Name", "Age", "Nation", "Club", "Position", "CA", "PA"]);
+    for (i, record) in records.iter().enumerate() {
+        let player = &record.player;
+        table.rows.push(vec![
+            (i + 1).to_string(),
+            display_name(record),
+            number(game_age(player).map(f64::from)),
+            country_name(player.nationality_id).unwrap_or("-").to_string(),
+            player.club_id.and_then(club_name).unwrap_or("-").to_string(),
+            player.position.as_deref().map_or("-", |p| get_position_short(p).unwrap_or(p)).to_string(),
+            number(player.ca.map(f64::from)),
+            number(player.pa.map(f64::from)),
+        ]);
+    }
+    table
+}
+
+/// Filters as compact JSON without the unset fields
+fn describe_filters(filters: Option<&PlayerFilters>) -> String {
+    let mut value = serde_json::to_value(filters).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.retain(|_, v| !v.is_null());
+        if fields.is_empty() {
+            return "none".to_string();
+        }
+    }
+    if value.is_null() {
+        return "none".to_string();
+    }
+    value.to_string()
+}
+
+/// Render statistics, top players and group breakdowns for a selection into a
+/// self-contained Markdown or HTML file. `template_path` may point to a custom
+/// template using the `{{title}}`, `{{filters}}`, `{{count}}`, `{{summary}}`, `{{top_players}}`,
+/// `{{nationalities}}` and `{{groups}}` placeholders.
+#[tauri::command]
+pub fn generate_report(
+    path: String,
+    format: ReportFormat,
+    filters: Option<PlayerFilters>,
+    template_path: Option<String>,
+    group_by: Option<Vec<GroupDimension>>,
+    top_limit: Option<usize>,
+) -> Result<(), String> {
+    println!("[REPORT] Generating {:?} report to {}", format, path);
+    let template = match template_path {
+        Some(ref template_path) => fs::read_to_string(template_path)
+            .map_err(|e| format!("Failed to read template {}: {}", template_path, e))?,
+        None => match format {
+            ReportFormat::Markdown => DEFAULT_MARKDOWN_TEMPLATE.to_string(),
+            ReportFormat::Html => DEFAULT_HTML_TEMPLATE.to_string(),
+        },
+    };
+    let filters = resolve_filters(filters)?;
+    let top_limit = top_limit.unwrap_or(DEFAULT_TOP_LIMIT);
+    let group_by = group_by.unwrap_or_else(|| vec![GroupDimension::Position, GroupDimension::BirthYear]);
+
+    // Every section reads the same selection, filtered under one lock
+    let filtered_players = {
+        let players = get_players().lock().map_err(|e| e.to_string())?;
+        filter_players(&players, filters.as_ref())?
+    };
+    let statistics = statistics_of(&filtered_players);
+    let top_players = top_players_of(&filtered_players, top_limit);
+
+    let mut summary = Table::new(None, &["", "Min", "Mean", "Median", "Max"]);
+    summary.rows.push(stats_row("CA", &statistics.ca_stats));
+    summary.rows.push(stats_row("PA", &statistics.pa_stats));
+    summary.rows.push(stats_row("Height (cm)", &statistics.height_stats));
+    summary.rows.push(stats_row("Weight (kg)", &statistics.weight_stats));
+
+    let top_players_section = [
+        top_table("Highest CA", &top_players.top_ca),
+        top_table("Highest PA", &top_players.top_pa),
+    ]
+    .iter()
+    .map(|table| table.render(format))
+    .collect::<Vec<_>>()
+    .join("\n");
+
+    let mut nations: Vec<(i32, usize)> = statistics.nationality_counts.iter().map(|(&id, &count)| (id, count)).collect();
+    nations.sort_by_key(|&(id, count)| (std::cmp::Reverse(count), id));
+    let mut nationalities = Table::new(None, &["Nation", "Players"]);
+    for (id, count) in nations.into_iter().take(NATIONALITY_ROWS) {
+        let name = country_name(id).map_or_else(|| format!("Country {}", id), str::to_string);
+        nationalities.rows.push(vec![name, count.to_string()]);
+    }
+
+    let aggregates = vec![
+        Aggregate { function: AggregateFunction::Count, field: None },
+        Aggregate { function: AggregateFunction::Mean, field: Some(NumericField::Ca) },
+        Aggregate { function: AggregateFunction::Mean, field: Some(NumericField::Pa) },
+        Aggregate { function: AggregateFunction::Max, field: Some(NumericField::Pa) },
+    ];
+    let mut groups = Vec::new();
+    for dimension in group_by {
+        let stats = group_stats_of(&filtered_players, &[dimension], &aggregates, DEFAULT_BAND_WIDTH);
+        let title = dimension.title();
+        let mut table = Table::new(Some(title.to_string()), &[title, "Players", "Mean CA", "Mean PA", "Max PA"]);
+        for cell in &stats.cells {
+            let mut row = vec![stats.rows[cell.row].label.clone()];
+            row.extend(cell.values.iter().map(|value| number(*value)));
+            table.rows.push(row);
+        }
+        groups.push(table.render(format));
+    }
+
+    let filters_text = describe_filters(filters.as_ref());
+    let filters_text = match format {
+        ReportFormat::Markdown => format!("`{}`", filters_text),
+        ReportFormat::Html => format!("<code>{}</code>", escape_html(&filters_text)),
+    };
+    let report = template
+        .replace("{{title}}", REPORT_TITLE)
+        .replace("{{filters}}", &filters_text)
+        .replace("{{count}}", &statistics.count.to_string())
+        .replace("{{summary}}", &summary.render(format))
+        .replace("{{top_players}}", &top_players_section)
+        .replace("{{nationalities}}", &nationalities.render(format))
+        .replace("{{groups}}", &groups.join("\n"));
+
+    fs::write(&path, report).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    println!("[REPORT] Wrote report for {} players", statistics.count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::player;
+
+    #[test]
+    fn tables_use_short_positions() {
+        let mut record = PlayerRecord { id: 1, player: player("Jan", "Kowalski"), relevance: None };
+        record.player.position = Some("DEFENDER_CENTRAL".to_string());
+        let table = top_table("Highest CA", std::slice::from_ref(&record));
+        assert_eq!(table.rows[0][5], "FB");
+
+        let breakdown = group_stats_of(&[record], &[GroupDimension::Position], &[], DEFAULT_BAND_WIDTH);
+        assert_eq!(breakdown.rows[0].label, "FB");
+        assert_eq!(breakdown.rows[0].key.as_deref(), Some("DEFENDER_CENTRAL"));
+    }
+}
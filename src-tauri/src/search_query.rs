@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+
+use crate::model::Player;
+use crate::transliteration::{needs_transliteration, transliterate};
+use crate::utils::{
+    club_ids_matching, country_ids_matching, get_position_short, matches_search_key, normalize_name,
+};
+
+/// A `field:value` term of the search box
+enum FieldTerm {
+    First(String),
+    Last(String),
+    Common(String),
+    City(String),
+    /// Club and nation names are resolved to IDs once, when the query is parsed
+    Club(HashSet<i32>),
+    Nation(HashSet<i32>),
+    Position(String),
+}
+
+/// Parsed `name_query`.
+/// Supports `first:`, `last:`, `common:`, `city:`, `club:`, `nation:` and `pos:` prefixes
+/// and quoted phrases (`last:"van dijk"`); everything else is a plain name search.
+pub struct SearchQuery {
+    /// Normalized free text, matched against the whole name like before
+    pub name: String,
+    terms: Vec<FieldTerm>,
+}
+
+/// Split on whitespace, keeping quoted phrases (also after a `field:` prefix) together
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Normalized text of a name field, with its Latin transliteration when needed
+fn field_key(value: &str) -> String {
+    let mut key = normalize_name(value);
+    if needs_transliteration(value) {
+        key.push(' ');
+        key.push_str(&normalize_name(&transliterate(value)));
+    }
+    key
+}
+
+fn text_matches(value: Option<&str>, term: &str) -> bool {
+    value.is_some_and(|v| matches_search_key(&field_key(v), term))
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> SearchQuery {
+        let mut name_parts = Vec::new();
+        let mut terms = Vec::new();
+
+        for token in tokenize(query) {
+            let term = token.split_once(':').and_then(|(prefix, value)| {
+                let value = value.trim();
+                if value.is_empty() {
+                    return None;
+                }
+                let text = normalize_name(value);
+                match prefix.to_lowercase().as_str() {
+                    "first" => Some(FieldTerm::First(text)),
+                    "last" => Some(FieldTerm::Last(text)),
+                    "common" => Some(FieldTerm::Common(text)),
+                    "city" => Some(FieldTerm::City(text)),
+                    "club" => Some(FieldTerm::Club(club_ids_matching(value))),
+                    "nation" => Some(FieldTerm::Nation(country_ids_matching(value))),
+                    "pos" => Some(FieldTerm::Position(text)),
+                    _ => None,
+                }
+            });
+            match term {
+                Some(term) => terms.push(term),
+                // Unknown prefixes are searched as plain text
+                None => name_parts.push(token),
+            }
+        }
+
+        SearchQuery {
+            name: normalize_name(&name_parts.join(" ")),
+            terms,
+        }
+    }
+
+    /// Check the field terms; the free text part is matched separately against the search key
+    pub fn matches_fields(&self, player: &Player) -> bool {
+        self.terms.iter().all(|term| match term {
+            FieldTerm::First(text) => text_matches(Some(&player.first_name), text),
+            FieldTerm::Last(text) => text_matches(Some(&player.last_name), text),
+            FieldTerm::Common(text) => text_matches(player.common_name.as_deref(), text),
+            FieldTerm::City(text) => text_matches(player.birth_city.as_deref(), text),
+            FieldTerm::Club(ids) => player.club_id.is_some_and(|id| ids.contains(&id)),
+            FieldTerm::Nation(ids) => ids.contains(&player.nationality_id),
+            // Short code ("ra", "ac") or the whole position name ("attacking midfielder central")
+            FieldTerm::Position(text) => player.position.as_deref().is_some_and(|pos| {
+                get_position_short(pos).is_some_and(|short| short.eq_ignore_ascii_case(text))
+                    || normalize_name(&pos.replace('_', " ")) == *text
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::player;
+
+    fn at(position: &str) -> Player {
+        let mut p = player("Jan", "Kowalski");
+        p.position = Some(position.to_string());
+        p
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_phrases() {
+        assert_eq!(tokenize(r#"last:"van dijk" jan"#), ["last:van dijk", "jan"]);
+        assert_eq!(tokenize("  a   b "), ["a", "b"]);
+    }
+
+    #[test]
+    fn unknown_prefix_is_plain_text() {
+        let query = SearchQuery::parse("foo:bar Jan");
+        assert_eq!(query.name, "foo:bar jan");
+        assert!(query.terms.is_empty());
+    }
+
+    #[test]
+    fn position_matches_short_code() {
+        let query = SearchQuery::parse("pos:ra");
+        assert!(query.matches_fields(&at("ATTACKING_MIDFIELDER_RIGHT_SIDE")));
+        assert!(!query.matches_fields(&at("MIDFIELDER_CENTRAL")));
+    }
+
+    #[test]
+    fn position_matches_whole_name_only() {
+        let query = SearchQuery::parse(r#"pos:"attacking midfielder central""#);
+        assert!(query.matches_fields(&at("ATTACKING_MIDFIELDER_CENTRAL")));
+        assert!(!SearchQuery::parse("pos:attacking").matches_fields(&at("ATTACKING_MIDFIELDER_CENTRAL")));
+    }
+
+    #[test]
+    fn field_terms_must_all_match() {
+        let mut p = player("Jan", "Van Dijk");
+        p.birth_city = Some("Kraków".to_string());
+        assert!(SearchQuery::parse(r#"last:"van dijk" city:krakow"#).matches_fields(&p));
+        assert!(!SearchQuery::parse("first:piotr city:krakow").matches_fields(&p));
+    }
+}
@@ -0,0 +1,351 @@
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::get_players;
+use crate::model::{ChartKind, NumericField, PlayerFilters, PlayerRecord};
+use crate::filter_presets::resolve_filters;
+use crate::player_filters::filter_players;
+use crate::player_statistics::{histogram_of, relationship_stats_of, statistics_of};
+use crate::utils::{country_name, escape_html};
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 400;
+/// Nations shown in the nationality chart
+const NATIONALITY_BARS: usize = 20;
+/// Grid size per axis of the height/weight scatter
+const SCATTER_GRID: usize = 30;
+/// Most tick labels drawn on one axis, whatever step rounding produced
+const MAX_TICKS: usize = 50;
+
+const MARGIN_LEFT: f64 = 60.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 70.0;
+
+const CA_COLOR: &str = "#4c78a8";
+const PA_COLOR: &str = "#f58518";
+const AXIS_COLOR: &str = "#444";
+const GRID_COLOR: &str = "#ddd";
+
+/// A data series of a bar or line chart
+struct Series {
+    name: &'static str,
+    color: &'static str,
+    values: Vec<f64>,
+}
+
+/// Round a tick step up to 1, 2 or 5 times a power of ten
+fn nice_step(range: f64, ticks: usize) -> f64 {
+    if range <= 0.0 {
+        return 1.0;
+    }
+    let raw = range / ticks as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|&step| step >= raw)
+        .unwrap_or(10.0 * magnitude);
+    step.max(f64::EPSILON)
+}
+
+/// SVG document with a plot area inside fixed margins
+struct Canvas {
+    width: f64,
+    height: f64,
+    body: String,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, title: &str) -> Canvas {
+        let mut canvas = Canvas {
+            width: width as f64,
+            height: height as f64,
+            body: String::new(),
+        };
+        canvas.text(canvas.width / 2.0, 24.0, title, "middle", 16, None);
+        canvas
+    }
+
+    fn plot_width(&self) -> f64 {
+        self.width - MARGIN_LEFT - MARGIN_RIGHT
+    }
+
+    fn plot_height(&self) -> f64 {
+        self.height - MARGIN_TOP - MARGIN_BOTTOM
+    }
+
+    fn plot_bottom(&self) -> f64 {
+        self.height - MARGIN_BOTTOM
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: &str, size: u32, rotate: Option<f64>) {
+        let transform = rotate.map_or_else(String::new, |angle| format!(" transform=\"rotate({} {:.1} {:.1})\"", angle, x, y));
+        let _ = writeln!(
+            self.body,
+            "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{}\" text-anchor=\"{}\"{}>{}</text>",
+            x, y, size, anchor, transform, escape_html(text)
+        );
+    }
+
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, color: &str, width: f64) {
+        let _ = writeln!(
+            self.body,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            x1, y1, x2, y2, color, width
+        );
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, color: &str) {
+        let _ = writeln!(
+            self.body,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+            x, y, width.max(0.0), height.max(0.0), color
+        );
+    }
+
+    /// Horizontal grid lines and labels for a y axis from `min` to `max`;
+    /// returns the function mapping a value to its y coordinate
+    fn y_axis(&mut self, min: f64, max: f64, label: &str) -> impl Fn(f64) -> f64 {
+        let step = nice_step(max - min, 5);
+        let top = (max / step).ceil() * step;
+        let bottom = (min / step).floor() * step;
+        let span = (top - bottom).max(step);
+        let (plot_bottom, plot_height) = (self.plot_bottom(), self.plot_height());
+        let scale = move |value: f64| plot_bottom - (value - bottom) / span * plot_height;
+
+        for tick in (0..MAX_TICKS).map(|i| bottom + i as f64 * step).take_while(|&tick| tick <= top + step / 2.0) {
+            let y = scale(tick);
+            self.line(MARGIN_LEFT, y, self.width - MARGIN_RIGHT, y, GRID_COLOR, 1.0);
+            self.text(MARGIN_LEFT - 6.0, y + 4.0, &format!("{}", tick), "end", 11, None);
+        }
+        self.line(MARGIN_LEFT, MARGIN_TOP, MARGIN_LEFT, plot_bottom, AXIS_COLOR, 1.0);
+        self.line(MARGIN_LEFT, plot_bottom, self.width - MARGIN_RIGHT, plot_bottom, AXIS_COLOR, 1.0);
+        self.text(16.0, MARGIN_TOP + plot_height / 2.0, label, "middle", 12, Some(-90.0));
+        scale
+    }
+
+    fn legend(&mut self, series: &[Series]) {
+        let mut x = MARGIN_LEFT;
+        for s in series {
+            self.rect(x, self.height - 18.0, 12.0, 12.0, s.color);
+            self.text(x + 16.0, self.height - 8.0, s.name, "start", 12, None);
+            x += 24.0 + s.name.len() as f64 * 8.0;
+        }
+    }
+
+    fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+             <g font-family=\"sans-serif\" fill=\"#222\">\n{}</g>\n</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+}
+
+/// Grouped vertical bars, one group per label
+fn bar_chart(canvas: &mut Canvas, labels: &[String], series: &[Series], rotate_labels: bool) {
+    let max = series.iter().flat_map(|s| s.values.iter().copied()).fold(0.0, f64::max);
+    let scale = canvas.y_axis(0.0, max.max(1.0), "Players");
+    let slot = canvas.plot_width() / labels.len().max(1) as f64;
+    let bar = slot * 0.8 / series.len().max(1) as f64;
+    // Thin out labels so they don't overlap
+    let every = ((labels.len() as f64 * 30.0) / canvas.plot_width()).ceil().max(1.0) as usize;
+
+    for (i, label) in labels.iter().enumerate() {
+        let x = MARGIN_LEFT + i as f64 * slot + slot * 0.1;
+        for (j, s) in series.iter().enumerate() {
+            let value = s.values.get(i).copied().unwrap_or(0.0);
+            let y = scale(value);
+            canvas.rect(x + j as f64 * bar, y, bar, canvas.plot_bottom() - y, s.color);
+        }
+        if i % every == 0 {
+            let label_x = x + slot * 0.4;
+            let label_y = canvas.plot_bottom() + 14.0;
+            if rotate_labels {
+                canvas.text(label_x, label_y, label, "end", 10, Some(-45.0));
+            } else {
+                canvas.text(label_x, label_y, label, "middle", 10, None);
+            }
+        }
+    }
+}
+
+/// CA and PA histograms. Ranged PA (stored as a negative value) has no place
+/// on the 0-199 scale, so those players are left out of the PA bars.
+fn ability_distribution(records: &[PlayerRecord], canvas: &mut Canvas) -> Result<(), String> {
+    let ca = histogram_of(records, NumericField::Ca, Some(10.0), None, Some(0.0), Some(199.0), None)?;
+    let rated: Vec<PlayerRecord> = records
+        .iter()
+        .filter(|record| record.player.pa.is_some_and(|pa| pa >= 0))
+        .cloned()
+        .collect();
+    let pa = histogram_of(&rated, NumericField::Pa, Some(10.0), None, Some(0.0), Some(199.0), None)?;
+    let labels: Vec<String> = ca.bins.iter().map(|bin| format!("{}", bin.start)).collect();
+    let series = [
+        Series { name: "CA", color: CA_COLOR, values: ca.bins.iter().map(|bin| bin.count as f64).collect() },
+        Series { name: "PA", color: PA_COLOR, values: pa.bins.iter().map(|bin| bin.count as f64).collect() },
+    ];
+    bar_chart(canvas, &labels, &series, false);
+    canvas.legend(&series);
+    canvas.text(canvas.width - MARGIN_RIGHT, canvas.height - 8.0, "Ranged PA not shown", "end", 10, None);
+    Ok(())
+}
+
+fn nationalities(records: &[PlayerRecord], canvas: &mut Canvas) -> Result<(), String> {
+    let statistics = statistics_of(records);
+    let mut nations: Vec<(i32, usize)> = statistics.nationality_counts.into_iter().collect();
+    nations.sort_by_key(|&(id, count)| (std::cmp::Reverse(count), id));
+    nations.truncate(NATIONALITY_BARS);
+    let labels: Vec<String> = nations
+        .iter()
+        .map(|&(id, _)| country_name(id).map_or_else(|| format!("Country {}", id), str::to_string))
+        .collect();
+    let series = [Series {
+        name: "Players",
+        color: CA_COLOR,
+        values: nations.iter().map(|&(_, count)| count as f64).collect(),
+    }];
+    bar_chart(canvas, &labels, &series, true);
+    Ok(())
+}
+
+fn birth_years(records: &[PlayerRecord], canvas: &mut Canvas) -> Result<(), String> {
+    let statistics = statistics_of(records);
+    let (first, last) = match (
+        statistics.birth_year_counts.keys().min(),
+        statistics.birth_year_counts.keys().max(),
+    ) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => {
+            canvas.text(canvas.width / 2.0, canvas.height / 2.0, "No players", "middle", 14, None);
+            return Ok(());
+        }
+    };
+    // Every year in the range, so gaps in the timeline show up as zeros
+    let points: Vec<(i32, f64)> = (first..=last)
+        .map(|year| (year, statistics.birth_year_counts.get(&year).copied().unwrap_or(0) as f64))
+        .collect();
+    let max = points.iter().map(|&(_, count)| count).fold(0.0, f64::max);
+    let scale = canvas.y_axis(0.0, max.max(1.0), "Players");
+    let step = canvas.plot_width() / points.len().max(2).saturating_sub(1) as f64;
+    let x_of = |i: usize| MARGIN_LEFT + i as f64 * step;
+
+    let path: Vec<String> = points
+        .iter()
+        .enumerate()
+        .map(|(i, &(_, count))| format!("{:.1},{:.1}", x_of(i), scale(count)))
+        .collect();
+    let _ = writeln!(
+        canvas.body,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+        path.join(" "),
+        CA_COLOR
+    );
+    let every = ((points.len() as f64 * 36.0) / canvas.plot_width()).ceil().max(1.0) as usize;
+    for (i, &(year, count)) in points.iter().enumerate() {
+        let _ = writeln!(canvas.body, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"/>", x_of(i), scale(count), CA_COLOR);
+        if i % every == 0 {
+            let y = canvas.plot_bottom() + 16.0;
+            canvas.text(x_of(i), y, &year.to_string(), "middle", 10, None);
+        }
+    }
+    Ok(())
+}
+
+fn height_weight(records: &[PlayerRecord], canvas: &mut Canvas) -> Result<(), String> {
+    let stats = relationship_stats_of(records, NumericField::Height, NumericField::Weight, SCATTER_GRID)?;
+    if stats.scatter.is_empty() {
+        canvas.text(canvas.width / 2.0, canvas.height / 2.0, "No players", "middle", 14, None);
+        return Ok(());
+    }
+    let min_x = stats.scatter.iter().map(|c| c.x_start).fold(f64::INFINITY, f64::min);
+    let max_x = stats.scatter.iter().map(|c| c.x_end).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = stats.scatter.iter().map(|c| c.y_start).fold(f64::INFINITY, f64::min);
+    let max_y = stats.scatter.iter().map(|c| c.y_end).fold(f64::NEG_INFINITY, f64::max);
+    let max_count = stats.scatter.iter().map(|c| c.count).max().unwrap_or(1) as f64;
+
+    let scale_y = canvas.y_axis(min_y, max_y, "Weight (kg)");
+    let plot_width = canvas.plot_width();
+    let scale_x = move |value: f64| MARGIN_LEFT + (value - min_x) / (max_x - min_x).max(1.0) * plot_width;
+
+    let step = nice_step(max_x - min_x, 8);
+    let first = (min_x / step).ceil() * step;
+    for tick in (0..MAX_TICKS).map(|i| first + i as f64 * step).take_while(|&tick| tick <= max_x) {
+        let y = canvas.plot_bottom() + 16.0;
+        canvas.text(scale_x(tick), y, &format!("{}", tick), "middle", 10, None);
+    }
+    let label_y = canvas.height - 30.0;
+    canvas.text(MARGIN_LEFT + plot_width / 2.0, label_y, "Height (cm)", "middle", 12, None);
+
+    for cell in &stats.scatter {
+        let radius = 2.0 + 8.0 * (cell.count as f64 / max_count).sqrt();
+        let _ = writeln!(
+            canvas.body,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"{}\" fill-opacity=\"0.6\"><title>{} players</title></circle>",
+            scale_x(cell.mean_x), scale_y(cell.mean_y), radius, CA_COLOR, cell.count
+        );
+    }
+    if let Some(line) = stats.regression {
+        let (x1, x2) = (min_x, max_x);
+        let (y1, y2) = (line.slope * x1 + line.intercept, line.slope * x2 + line.intercept);
+        canvas.line(scale_x(x1), scale_y(y1), scale_x(x2), scale_y(y2), PA_COLOR, 2.0);
+    }
+    Ok(())
+}
+
+/// Render one of the Analyst charts for a filtered selection to an SVG file,
+/// without the frontend, so reports and headless exports can embed it
+#[tauri::command]
+pub fn render_chart(
+    chart: ChartKind,
+    path: String,
+    filters: Option<PlayerFilters>,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<(), String> {
+    println!("[CHART] Rendering {:?} to {}", chart, path);
+    let filters = resolve_filters(filters)?;
+    let title = match chart {
+        ChartKind::AbilityDistribution => "CA / PA distribution",
+        ChartKind::Nationalities => "Players by nationality",
+        ChartKind::BirthYears => "Players by birth year",
+        ChartKind::HeightWeight => "Height vs weight",
+    };
+    // Every series of a chart reads the same selection, filtered under one lock
+    let records = {
+        let players = get_players().lock().map_err(|e| e.to_string())?;
+        filter_players(&players, filters.as_ref())?
+    };
+    let mut canvas = Canvas::new(width.unwrap_or(DEFAULT_WIDTH), height.unwrap_or(DEFAULT_HEIGHT), title);
+    match chart {
+        ChartKind::AbilityDistribution => ability_distribution(&records, &mut canvas)?,
+        ChartKind::Nationalities => nationalities(&records, &mut canvas)?,
+        ChartKind::BirthYears => birth_years(&records, &mut canvas)?,
+        ChartKind::HeightWeight => height_weight(&records, &mut canvas)?,
+    }
+    fs::write(&path, canvas.finish()).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_are_round_numbers() {
+        assert_eq!(nice_step(100.0, 5), 20.0);
+        assert_eq!(nice_step(7.0, 5), 2.0);
+        assert_eq!(nice_step(0.0, 5), 1.0);
+    }
+
+    #[test]
+    fn ticks_are_bounded() {
+        // The step is far below the precision of the values, so adding it never moves the tick
+        let mut canvas = Canvas::new(DEFAULT_WIDTH, DEFAULT_HEIGHT, "test");
+        let _scale = canvas.y_axis(1e17, 1e17 + 1.0, "Players");
+        assert!(canvas.body.matches("<text").count() <= MAX_TICKS + 2);
+    }
+}
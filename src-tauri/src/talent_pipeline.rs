@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+use crate::get_players;
+use crate::filter_presets::resolve_filters;
+use crate::model::{
+    GroupCount, GroupDimension, Player, PlayerFilters, PlayerRecord, RecordType, SeasonIntake, TalentPipeline,
+};
+use crate::player_dimensions::{GroupValue, DEFAULT_BAND_WIDTH};
+use crate::player_filters::filter_players;
+
+/// Age at which a regen joins a youth intake when no nation-specific age is given
+pub const DEFAULT_INTAKE_AGE: i32 = 15;
+/// Intake ages a projection accepts, overall or per nation
+const INTAKE_AGES: RangeInclusive<i32> = 10..=20;
+/// Most seasons one projection may span, from the first season to the last arrival
+const MAX_PIPELINE_SEASONS: i64 = 200;
+
+/// Counts per group value, in the dimension's display order
+fn breakdown(dimension: GroupDimension, players: &[&Player]) -> Vec<GroupCount> {
+    let mut counts: HashMap<GroupValue, usize> = HashMap::new();
+    for player in players {
+        *counts.entry(dimension.value(player, DEFAULT_BAND_WIDTH)).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(GroupValue, usize)> = counts.into_iter().collect();
+    counts.sort_by(|(a, _), (b, _)| dimension.compare(a, b, DEFAULT_BAND_WIDTH));
+    counts
+        .into_iter()
+        .map(|(value, count)| GroupCount {
+            group: dimension.key(&value, DEFAULT_BAND_WIDTH),
+            count,
+        })
+        .collect()
+}
+
+/// Project in which in-game season each regen first appears.
+/// Birth years are in-game years (as stored after conversion on load). A regen
+/// joins the intake of the season starting in `birth year + intake age`; the
+/// save's first season starts in `game_year - 1`, and regens already old
+/// enough by then are counted in that first season.
+#[tauri::command]
+pub fn project_talent_pipeline(
+    filters: Option<PlayerFilters>,
+    game_year: i32,
+    mod_start_year: i32,
+    intake_age: Option<i32>,
+    intake_age_by_nation: Option<HashMap<i32, i32>>,
+) -> Result<TalentPipeline, String> {
+    let intake_age = intake_age.unwrap_or(DEFAULT_INTAKE_AGE);
+    let intake_age_by_nation = intake_age_by_nation.unwrap_or_default();
+    for &age in std::iter::once(&intake_age).chain(intake_age_by_nation.values()) {
+        if !INTAKE_AGES.contains(&age) {
+            return Err(format!(
+                "Intake age must be between {} and {}, got {}",
+                INTAKE_AGES.start(), INTAKE_AGES.end(), age
+            ));
+        }
+    }
+    let first_season = game_year - 1;
+    let year_offset = game_year - 1 - mod_start_year;
+    println!("Projecting talent pipeline from season {} (intake age {})", first_season, intake_age);
+    let filters = resolve_filters(filters)?;
+
+    let players = get_players().lock().unwrap();
+    let filtered_players = filter_players(&players, filters.as_ref())?;
+    pipeline_of(&filtered_players, first_season, year_offset, intake_age, &intake_age_by_nation)
+}
+
+/// Arrivals per season of an already filtered selection; intake ages are
+/// already checked against `INTAKE_AGES`
+fn pipeline_of(
+    filtered_players: &[PlayerRecord],
+    first_season: i32,
+    year_offset: i32,
+    intake_age: i32,
+    intake_age_by_nation: &HashMap<i32, i32>,
+) -> Result<TalentPipeline, String> {
+    let mut arrivals: BTreeMap<i32, Vec<&Player>> = BTreeMap::new();
+    for record in filtered_players {
+        let player = &record.player;
+        if player.record_type != RecordType::DetailedFutureRegen {
+            continue;
+        }
+        let birth_year = player.birth_date.year();
+        let age = intake_age_by_nation.get(&player.nationality_id).copied().unwrap_or(intake_age);
+        let season = birth_year.saturating_add(age).max(first_season);
+        arrivals.entry(season).or_default().push(player);
+    }
+
+    let last_season = arrivals.keys().next_back().copied().unwrap_or(first_season);
+    let span = last_season as i64 - first_season as i64 + 1;
+    if span > MAX_PIPELINE_SEASONS {
+        return Err(format!(
+            "Arrivals run from {} to {}, more than {} seasons; narrow the filters",
+            first_season, last_season, MAX_PIPELINE_SEASONS
+        ));
+    }
+    let mut seasons = Vec::new();
+    let mut empty_seasons = Vec::new();
+    for season in first_season..=last_season {
+        let arrived = arrivals.get(&season).map(Vec::as_slice).unwrap_or_default();
+        if arrived.is_empty() {
+            empty_seasons.push(season);
+        }
+        seasons.push(SeasonIntake {
+            season,
+            label: format!("{}/{:02}", season, (season + 1).rem_euclid(100)),
+            real_year: season - year_offset,
+            count: arrived.len(),
+            by_nationality: breakdown(GroupDimension::Nationality, arrived),
+            by_club: breakdown(GroupDimension::Club, arrived),
+            by_position: breakdown(GroupDimension::Position, arrived),
+            by_pa_band: breakdown(GroupDimension::PaBand, arrived),
+        });
+    }
+
+    Ok(TalentPipeline {
+        seasons,
+        empty_seasons,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Date;
+    use crate::test_support::player;
+
+    fn regen(id: usize, birth_year: i32, nationality_id: i32) -> PlayerRecord {
+        let mut player = player("Jan", "Kowalski");
+        player.birth_date = Date::new(1, 1, birth_year).unwrap();
+        player.nationality_id = nationality_id;
+        PlayerRecord { id, player, relevance: None }
+    }
+
+    #[test]
+    fn regens_arrive_at_their_intake_age() {
+        let records = [regen(1, 2000, 1), regen(2, 2010, 1), regen(3, 2010, 2)];
+        let by_nation = HashMap::from([(2, 17)]);
+        let pipeline = pipeline_of(&records, 2023, 0, 15, &by_nation).unwrap();
+        let counts: Vec<(i32, usize)> = pipeline.seasons.iter().map(|s| (s.season, s.count)).collect();
+        // Already old enough by the first season, then 2010 + 15, then 2010 + 17
+        assert_eq!(counts, vec![(2023, 1), (2024, 0), (2025, 1), (2026, 0), (2027, 1)]);
+        assert_eq!(pipeline.empty_seasons, vec![2024, 2026]);
+    }
+
+    #[test]
+    fn far_future_arrivals_are_rejected() {
+        let records = [regen(1, 9999, 1)];
+        assert!(pipeline_of(&records, 2023, 0, 15, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn intake_ages_are_checked() {
+        assert!(project_talent_pipeline(None, 2024, 2023, Some(0), None).is_err());
+        assert!(project_talent_pipeline(None, 2024, 2023, None, Some(HashMap::from([(1, i32::MAX)]))).is_err());
+    }
+}
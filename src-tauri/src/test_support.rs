@@ -0,0 +1,26 @@
+use crate::model::{Date, Player, RecordType};
+
+/// Minimal future regen for unit tests; tweak the fields a test cares about
+pub fn player(first_name: &str, last_name: &str) -> Player {
+    Player {
+        record_type: RecordType::DetailedFutureRegen,
+        first_name: first_name.to_string(),
+        common_name: None,
+        last_name: last_name.to_string(),
+        birth_date: Date::new(1, 1, 2008).unwrap(),
+        nationality_id: 1,
+        favourite_team_id: None,
+        ethnicity: 0,
+        skin_tone: 0,
+        hair_color: 0,
+        height: 180,
+        weight: 75,
+        preferred_foot: None,
+        position: None,
+        favourite_number: None,
+        birth_city: None,
+        ca: None,
+        pa: None,
+        club_id: None,
+    }
+}
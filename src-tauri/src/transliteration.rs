@@ -0,0 +1,236 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Two-letter sequences that transliterate as a unit
+fn transliterate_digraph(first: char, second: char) -> Option<&'static str> {
+    match (first, second) {
+        // Greek
+        ('ο', 'υ') => Some("ou"),
+        ('α', 'υ') => Some("av"),
+        ('ε', 'υ') => Some("ev"),
+        ('μ', 'π') => Some("mp"),
+        ('ν', 'τ') => Some("nt"),
+        ('γ', 'κ') => Some("gk"),
+        ('γ', 'γ') => Some("ng"),
+        // Armenian
+        ('ո', 'ւ') => Some("u"),
+        _ => None,
+    }
+}
+
+/// Latin transliteration of a single lowercase letter
+fn transliterate_char(c: char) -> Option<&'static str> {
+    let latin = match c {
+        // Cyrillic (Russian, Ukrainian, Belarusian, Serbian, Macedonian, Bulgarian)
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'ґ' => "g",
+        'д' => "d",
+        'ђ' => "dj",
+        'ѓ' => "gj",
+        'е' => "e",
+        'ё' => "yo",
+        'є' => "ye",
+        'ж' => "zh",
+        'з' => "z",
+        'ѕ' => "dz",
+        'и' => "i",
+        'і' => "i",
+        'ї' => "yi",
+        'й' => "y",
+        'ј' => "j",
+        'к' => "k",
+        'л' => "l",
+        'љ' => "lj",
+        'м' => "m",
+        'н' => "n",
+        'њ' => "nj",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'ћ' => "c",
+        'ќ' => "kj",
+        'у' => "u",
+        'ў' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'џ' => "dz",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' => "",
+        'ы' => "y",
+        'ь' => "",
+        'э' => "e",
+        'ю' => "yu",
+        'я' => "ya",
+        // Greek
+        'α' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' => "e",
+        'ζ' => "z",
+        'η' => "i",
+        'θ' => "th",
+        'ι' => "i",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        'ω' => "o",
+        // Georgian (Mkhedruli, national system without apostrophes)
+        'ა' => "a",
+        'ბ' => "b",
+        'გ' => "g",
+        'დ' => "d",
+        'ე' => "e",
+        'ვ' => "v",
+        'ზ' => "z",
+        'თ' => "t",
+        'ი' => "i",
+        'კ' => "k",
+        'ლ' => "l",
+        'მ' => "m",
+        'ნ' => "n",
+        'ო' => "o",
+        'პ' => "p",
+        'ჟ' => "zh",
+        'რ' => "r",
+        'ს' => "s",
+        'ტ' => "t",
+        'უ' => "u",
+        'ფ' => "p",
+        'ქ' => "k",
+        'ღ' => "gh",
+        'ყ' => "q",
+        'შ' => "sh",
+        'ჩ' => "ch",
+        'ც' => "ts",
+        'ძ' => "dz",
+        'წ' => "ts",
+        'ჭ' => "ch",
+        'ხ' => "kh",
+        'ჯ' => "j",
+        'ჰ' => "h",
+        // Armenian
+        'ա' => "a",
+        'բ' => "b",
+        'գ' => "g",
+        'դ' => "d",
+        'ե' => "e",
+        'զ' => "z",
+        'է' => "e",
+        'ը' => "y",
+        'թ' => "t",
+        'ժ' => "zh",
+        'ի' => "i",
+        'լ' => "l",
+        'խ' => "kh",
+        'ծ' => "ts",
+        'կ' => "k",
+        'հ' => "h",
+        'ձ' => "dz",
+        'ղ' => "gh",
+        'ճ' => "ch",
+        'մ' => "m",
+        'յ' => "y",
+        'ն' => "n",
+        'շ' => "sh",
+        'ո' => "o",
+        'չ' => "ch",
+        'պ' => "p",
+        'ջ' => "j",
+        'ռ' => "r",
+        'ս' => "s",
+        'վ' => "v",
+        'տ' => "t",
+        'ր' => "r",
+        'ց' => "ts",
+        'ւ' => "v",
+        'փ' => "p",
+        'ք' => "k",
+        'օ' => "o",
+        'ֆ' => "f",
+        'և' => "yev",
+        _ => return None,
+    };
+    Some(latin)
+}
+
+/// Letter lookup that falls back to the base letter, so accented Greek
+/// vowels (ά, ή, ΐ...) transliterate like their plain forms
+fn lookup(c: char) -> Option<&'static str> {
+    transliterate_char(c).or_else(|| transliterate_char(base_letter(c)))
+}
+
+fn base_letter(c: char) -> char {
+    std::iter::once(c).nfd().next().unwrap_or(c)
+}
+
+/// Whether the text contains letters from a script we transliterate
+pub fn needs_transliteration(s: &str) -> bool {
+    s.chars().any(|c| matches!(c,
+        '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' // Greek
+        | '\u{0400}'..='\u{052F}'                         // Cyrillic
+        | '\u{0531}'..='\u{058F}'                         // Armenian
+        | '\u{10A0}'..='\u{10FF}' | '\u{1C90}'..='\u{1CBF}' // Georgian
+    ))
+}
+
+/// Lowercase Latin transliteration of Cyrillic, Greek, Georgian and Armenian text:
+/// "Шевченко" -> "shevchenko", "Γεώργιος" -> "georgios".
+/// Characters from other scripts are kept as they are (lowercased).
+pub fn transliterate(s: &str) -> String {
+    let chars: Vec<char> = s.nfc().flat_map(char::to_lowercase).collect();
+    let mut result = String::with_capacity(s.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = chars.get(i + 1) {
+            if let Some(latin) = transliterate_digraph(base_letter(chars[i]), base_letter(*next)) {
+                result.push_str(latin);
+                i += 2;
+                continue;
+            }
+        }
+        match lookup(chars[i]) {
+            Some(latin) => result.push_str(latin),
+            None => result.push(chars[i]),
+        }
+        i += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transliterates_each_script() {
+        assert_eq!(transliterate("Шевченко"), "shevchenko");
+        assert_eq!(transliterate("Γεώργιος"), "georgios");
+        assert_eq!(transliterate("Παπαδόπουλος"), "papadopoulos");
+    }
+
+    #[test]
+    fn other_scripts_are_only_lowercased() {
+        assert_eq!(transliterate("Müller"), "müller");
+        assert!(!needs_transliteration("Müller"));
+        assert!(needs_transliteration("Müller Шевченко"));
+    }
+}
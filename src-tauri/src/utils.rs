@@ -1,7 +1,10 @@
-use crate::model::PlayerRecord;
-use std::collections::HashMap;
+use crate::model::Date;
+use crate::transliteration::{needs_transliteration, transliterate};
+use std::collections::{HashMap, HashSet};
 use once_cell::sync::Lazy;
 use serde::Deserialize;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Deserialize)]
 struct CountryData {
@@ -38,51 +41,38 @@ static CLUB_MAP: Lazy<HashMap<i32, String>> = Lazy::new(|| {
     }
 });
 
-pub fn get_birth_year(birth_date: &str) -> Option<i32> {
-    // Parse birth date in DD/MM/YYYY format
-    let parts: Vec<&str> = birth_date.split('/').collect();
-    if parts.len() == 3 {
-        parts[2].parse().ok()
-    } else {
-        None
-    }
+/// Escape text for HTML and SVG output
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
-pub fn get_birth_month(birth_date: &str) -> Option<u32> {
-    // Parse birth date in DD/MM/YYYY format
-    let parts: Vec<&str> = birth_date.split('/').collect();
-    if parts.len() == 3 {
-        parts[1].parse().ok()
-    } else {
-        None
-    }
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
 }
 
-/// Parse birth date to (day, month, year) tuple
-pub fn parse_birth_date(birth_date: &str) -> Option<(u32, u32, i32)> {
-    let parts: Vec<&str> = birth_date.split('/').collect();
-    if parts.len() == 3 {
-        let day: u32 = parts[0].parse().ok()?;
-        let month: u32 = parts[1].parse().ok()?;
-        let year: i32 = parts[2].parse().ok()?;
-        Some((day, month, year))
-    } else {
-        None
-    }
+pub fn days_in_month(month: u32, year: i32) -> Option<u32> {
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => return None,
+    };
+    Some(days)
 }
 
 /// Check if a birth date falls within the specified date range (within a single year)
 pub fn is_birth_date_in_range(
-    birth_date: &str,
+    birth_date: Date,
     from_day: Option<u32>,
     from_month: Option<u32>,
     to_day: Option<u32>,
     to_month: Option<u32>,
 ) -> bool {
-    let (day, month, _year) = match parse_birth_date(birth_date) {
-        Some(d) => d,
-        None => return false,
-    };
+    let (day, month) = (birth_date.day(), birth_date.month());
     
     let from_d = from_day.unwrap_or(1);
     let from_m = from_month.unwrap_or(1);
@@ -97,7 +87,72 @@ pub fn is_birth_date_in_range(
     date_val >= from_val && date_val <= to_val
 }
 
-fn get_position_rank(position: &str) -> i32 {
+pub fn country_name(id: i32) -> Option<&'static str> {
+    COUNTRY_MAP.get(&id).map(String::as_str)
+}
+
+pub fn club_name(id: i32) -> Option<&'static str> {
+    CLUB_MAP.get(&id).map(String::as_str)
+}
+
+/// PA minus CA, when both are set
+pub fn pa_ca_gap(player: &crate::model::Player) -> Option<i32> {
+    Some(player.pa? - player.ca?)
+}
+
+/// In-game age at the configured reference date, when one is set
+pub fn game_age(player: &crate::model::Player) -> Option<i32> {
+    crate::age_reference().map(|reference| player.birth_date.age_on(reference))
+}
+
+/// Body mass index, when both height and weight are set
+pub fn bmi(player: &crate::model::Player) -> Option<f64> {
+    if player.height <= 0 || player.weight <= 0 {
+        return None;
+    }
+    let meters = player.height as f64 / 100.0;
+    Some(player.weight as f64 / (meters * meters))
+}
+
+/// IDs of every country whose name contains the (normalized) query
+pub fn country_ids_matching(query: &str) -> HashSet<i32> {
+    let query = normalize_name(query);
+    COUNTRY_MAP
+        .iter()
+        .filter(|(_, name)| normalize_name(name).contains(&query))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// IDs of every club whose name contains the (normalized) query
+pub fn club_ids_matching(query: &str) -> HashSet<i32> {
+    let query = normalize_name(query);
+    CLUB_MAP
+        .iter()
+        .filter(|(_, name)| normalize_name(name).contains(&query))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// Short position codes as shown in the UI (see `constants.ts`)
+pub fn get_position_short(position: &str) -> Option<&'static str> {
+    match position {
+        "GOALKEEPER" => Some("GK"),
+        "DEFENDER_LEFT_SIDE" => Some("LB"),
+        "DEFENDER_RIGHT_SIDE" => Some("RB"),
+        "DEFENDER_CENTRAL" => Some("FB"),
+        "MIDFIELDER_LEFT_SIDE" => Some("LM"),
+        "MIDFIELDER_RIGHT_SIDE" => Some("RM"),
+        "MIDFIELDER_CENTRAL" => Some("MC"),
+        "ATTACKING_MIDFIELDER_LEFT_SIDE" => Some("LA"),
+        "ATTACKING_MIDFIELDER_RIGHT_SIDE" => Some("RA"),
+        "ATTACKING_MIDFIELDER_CENTRAL" => Some("AM"),
+        "ATTACKER_CENTRAL" => Some("AC"),
+        _ => None,
+    }
+}
+
+pub fn get_position_rank(position: &str) -> i32 {
     // Extract base position if it has modifiers (though current data seems to be exact strings)
     // But let's match exact strings first as per constants.ts
     match position {
@@ -116,203 +171,91 @@ fn get_position_rank(position: &str) -> i32 {
     }
 }
 
-use std::cmp::Ordering;
-
-pub fn sort_players(mut players: Vec<PlayerRecord>, sort_criteria: &[String]) -> Vec<PlayerRecord> {
-    println!("Sorting players by: {:?}", sort_criteria);
-    
-    players.sort_by(|a, b| {
-        for criterion in sort_criteria {
-            let ordering = match criterion.as_str() {
-                "ca_desc" => {
-                    let ca_a = a.player.ca.unwrap_or(0);
-                    let ca_b = b.player.ca.unwrap_or(0);
-                    ca_b.cmp(&ca_a)
-                },
-                "ca_asc" => {
-                    let ca_a = a.player.ca.unwrap_or(0);
-                    let ca_b = b.player.ca.unwrap_or(0);
-                    ca_a.cmp(&ca_b)
-                },
-                "pa_desc" => {
-                    let pa_a = a.player.pa.unwrap_or(0);
-                    let pa_b = b.player.pa.unwrap_or(0);
-                    pa_b.cmp(&pa_a)
-                },
-                "pa_asc" => {
-                    let pa_a = a.player.pa.unwrap_or(0);
-                    let pa_b = b.player.pa.unwrap_or(0);
-                    pa_a.cmp(&pa_b)
-                },
-                "age_desc" => {
-                    // Older first: lower year = older, but within same year, lower month/day = older
-                    let date_a = parse_birth_date(&a.player.birth_date).unwrap_or((0, 0, 0));
-                    let date_b = parse_birth_date(&b.player.birth_date).unwrap_or((0, 0, 0));
-                    // Compare year first (ascending for older), then month, then day
-                    match date_a.2.cmp(&date_b.2) {
-                        Ordering::Equal => {
-                            match date_a.1.cmp(&date_b.1) {
-                                Ordering::Equal => date_a.0.cmp(&date_b.0),
-                                other => other
-                            }
-                        },
-                        other => other
-                    }
-                },
-                "age_asc" => {
-                    // Younger first: higher year = younger, but within same year, higher month/day = younger
-                    let date_a = parse_birth_date(&a.player.birth_date).unwrap_or((0, 0, 0));
-                    let date_b = parse_birth_date(&b.player.birth_date).unwrap_or((0, 0, 0));
-                    // Compare year first (descending for younger), then month desc, then day desc
-                    match date_b.2.cmp(&date_a.2) {
-                        Ordering::Equal => {
-                            match date_b.1.cmp(&date_a.1) {
-                                Ordering::Equal => date_b.0.cmp(&date_a.0),
-                                other => other
-                            }
-                        },
-                        other => other
-                    }
-                },
-                "name_asc" => {
-                    let name_a = format!("{} {}", a.player.first_name, a.player.last_name);
-                    let name_b = format!("{} {}", b.player.first_name, b.player.last_name);
-                    name_a.cmp(&name_b)
-                },
-                "name_desc" => {
-                    let name_a = format!("{} {}", a.player.first_name, a.player.last_name);
-                    let name_b = format!("{} {}", b.player.first_name, b.player.last_name);
-                    name_b.cmp(&name_a)
-                },
-                "club_asc" => {
-                    let empty = String::new();
-                    let club_a = a.player.club_id.and_then(|id| CLUB_MAP.get(&id)).unwrap_or(&empty);
-                    let club_b = b.player.club_id.and_then(|id| CLUB_MAP.get(&id)).unwrap_or(&empty);
-                    club_a.cmp(club_b)
-                },
-                "club_desc" => {
-                    let empty = String::new();
-                    let club_a = a.player.club_id.and_then(|id| CLUB_MAP.get(&id)).unwrap_or(&empty);
-                    let club_b = b.player.club_id.and_then(|id| CLUB_MAP.get(&id)).unwrap_or(&empty);
-                    club_b.cmp(club_a)
-                },
-                "nationality_asc" => {
-                    let empty = String::new();
-                    let nat_a = COUNTRY_MAP.get(&a.player.nationality_id).unwrap_or(&empty);
-                    let nat_b = COUNTRY_MAP.get(&b.player.nationality_id).unwrap_or(&empty);
-                    nat_a.cmp(nat_b)
-                },
-                "nationality_desc" => {
-                    let empty = String::new();
-                    let nat_a = COUNTRY_MAP.get(&a.player.nationality_id).unwrap_or(&empty);
-                    let nat_b = COUNTRY_MAP.get(&b.player.nationality_id).unwrap_or(&empty);
-                    nat_b.cmp(nat_a)
-                },
-                "position_asc" => {
-                    let pos_a = a.player.position.as_deref().unwrap_or("");
-                    let pos_b = b.player.position.as_deref().unwrap_or("");
-                    let rank_a = get_position_rank(pos_a);
-                    let rank_b = get_position_rank(pos_b);
-                    if rank_a != rank_b {
-                        rank_a.cmp(&rank_b)
-                    } else {
-                        pos_a.cmp(pos_b)
-                    }
-                },
-                "position_desc" => {
-                    let pos_a = a.player.position.as_deref().unwrap_or("");
-                    let pos_b = b.player.position.as_deref().unwrap_or("");
-                    let rank_a = get_position_rank(pos_a);
-                    let rank_b = get_position_rank(pos_b);
-                    if rank_a != rank_b {
-                        rank_b.cmp(&rank_a)
-                    } else {
-                        pos_b.cmp(pos_a)
-                    }
-                },
-                "height_asc" => {
-                    a.player.height.cmp(&b.player.height)
-                },
-                "height_desc" => {
-                    b.player.height.cmp(&a.player.height)
-                },
-                "weight_asc" => {
-                    a.player.weight.cmp(&b.player.weight)
-                },
-                "weight_desc" => {
-                    b.player.weight.cmp(&a.player.weight)
-                },
-                "foot_asc" => {
-                    let foot_a = a.player.preferred_foot.unwrap_or(0);
-                    let foot_b = b.player.preferred_foot.unwrap_or(0);
-                    foot_a.cmp(&foot_b)
-                },
-                "foot_desc" => {
-                    let foot_a = a.player.preferred_foot.unwrap_or(0);
-                    let foot_b = b.player.preferred_foot.unwrap_or(0);
-                    foot_b.cmp(&foot_a)
-                },
-                _ => Ordering::Equal,
-            };
-            
-            if ordering != Ordering::Equal {
-                return ordering;
-            }
-        }
-        Ordering::Equal
-    });
-    
-    println!("Sorted {} players", players.len());
-    players
+/// Latin letters that carry no decomposable diacritic and need an explicit mapping
+fn fold_special_letter(c: char) -> Option<&'static str> {
+    let folded = match c {
+        'ß' => "ss",
+        'ẞ' => "SS",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ø' => "o",
+        'Ø' => "O",
+        'ł' => "l",
+        'Ł' => "L",
+        'đ' => "d",
+        'Đ' => "D",
+        'ð' => "d",
+        'Ð' => "D",
+        'þ' => "th",
+        'Þ' => "TH",
+        'ı' => "i",
+        'ħ' => "h",
+        'Ħ' => "H",
+        'ŋ' => "n",
+        'Ŋ' => "N",
+        'ŧ' => "t",
+        'Ŧ' => "T",
+        'ĸ' => "k",
+        'ſ' => "s",
+        'ƒ' => "f",
+        'ɨ' => "i",
+        'ʉ' => "u",
+        _ => return None,
+    };
+    Some(folded)
 }
 
+/// Strip diacritics while keeping letter case: "Şükür" -> "Sukur", "Džeko" -> "Dzeko".
+/// Uses Unicode compatibility decomposition, drops combining marks and maps
+/// letters that don't decompose (ß, æ, œ, ł, đ, ð, þ, ı, ...) by hand.
 pub fn remove_accents(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ą' => 'a',
-            'Á' | 'À' | 'Â' | 'Ä' | 'Ã' | 'Å' | 'Ą' => 'A',
-            'ć' | 'č' | 'ç' => 'c',
-            'Ć' | 'Č' | 'Ç' => 'C',
-            'é' | 'è' | 'ê' | 'ë' | 'ę' => 'e',
-            'É' | 'È' | 'Ê' | 'Ë' | 'Ę' => 'E',
-            'í' | 'ì' | 'î' | 'ï' => 'i',
-            'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
-            'ł' => 'l',
-            'Ł' => 'L',
-            'ñ' | 'ń' => 'n',
-            'Ñ' | 'Ń' => 'N',
-            'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' => 'o',
-            'Ó' | 'Ò' | 'Ô' | 'Ö' | 'Õ' | 'Ø' => 'O',
-            'ś' | 'š' => 's',
-            'Ś' | 'Š' => 'S',
-            'ú' | 'ù' | 'û' | 'ü' => 'u',
-            'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
-            'ý' | 'ÿ' => 'y',
-            'Ý' | 'Ÿ' => 'Y',
-            'ź' | 'ż' | 'ž' => 'z',
-            'Ź' | 'Ż' | 'Ž' => 'Z',
-            _ => c,
-        })
-        .collect()
+    let mut result = String::with_capacity(s.len());
+    for c in s.nfkd() {
+        if is_combining_mark(c) {
+            continue;
+        }
+        match fold_special_letter(c) {
+            Some(folded) => result.push_str(folded),
+            None => result.push(c),
+        }
+    }
+    result
 }
 
-pub fn matches_search_query(player: &crate::model::Player, query: &str) -> bool {
-    if query.is_empty() {
-        return true;
-    }
+/// Accent and case insensitive form of a name, shared by search, dedupe and name sorting
+pub fn normalize_name(s: &str) -> String {
+    remove_accents(s).to_lowercase()
+}
 
-    let normalized_query = remove_accents(query).to_lowercase();
-    let full_name = remove_accents(&format!(
+/// Normalized "first last common" name used as the search key for a player.
+/// Names in Cyrillic, Greek, Georgian or Armenian script also get their Latin
+/// transliteration appended, so "Shevchenko" finds "Шевченко".
+pub fn player_search_key(player: &crate::model::Player) -> String {
+    let name = format!(
         "{} {} {}",
         player.first_name,
         player.last_name,
         player.common_name.as_deref().unwrap_or("")
-    ))
-    .to_lowercase();
+    );
+    let mut key = normalize_name(&name);
+    if needs_transliteration(&name) {
+        key.push(' ');
+        key.push_str(&normalize_name(&transliterate(&name)));
+    }
+    key
+}
+
+/// Match an already normalized query against a precomputed search key
+pub fn matches_search_key(search_key: &str, normalized_query: &str) -> bool {
+    if normalized_query.is_empty() {
+        return true;
+    }
 
     if normalized_query.contains('*') {
         let parts: Vec<&str> = normalized_query.split('*').collect();
-        let mut current_search_text = full_name.as_str();
+        let mut current_search_text = search_key;
 
         for part in parts {
             if part.is_empty() {
@@ -326,6 +269,95 @@ pub fn matches_search_query(player: &crate::model::Player, query: &str) -> bool
         }
         true
     } else {
-        full_name.contains(&normalized_query)
+        search_key.contains(normalized_query)
+    }
+}
+
+/// Levenshtein distance between two strings, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Similarity of one query word to one name word, between 0 and 1
+fn word_similarity(query_word: &str, name_word: &str) -> f64 {
+    if query_word == name_word {
+        1.0
+    } else if name_word.starts_with(query_word) {
+        0.9
+    } else if name_word.contains(query_word) {
+        0.8
+    } else {
+        let max_len = query_word.chars().count().max(name_word.chars().count());
+        1.0 - edit_distance(query_word, name_word) as f64 / max_len as f64
+    }
+}
+
+/// How well a normalized query matches a search key, between 0 and 1.
+/// Every query word is scored against its closest name word (first, last or common)
+/// and the scores are averaged, so a single typo still ranks high.
+pub fn name_relevance(search_key: &str, normalized_query: &str) -> f64 {
+    let name_words: Vec<&str> = search_key.split_whitespace().collect();
+    let query_words: Vec<&str> = normalized_query
+        .split(|c: char| c.is_whitespace() || c == '*')
+        .filter(|w| !w.is_empty())
+        .collect();
+    if query_words.is_empty() {
+        return 1.0;
+    }
+    if name_words.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = query_words
+        .iter()
+        .map(|q| name_words.iter().map(|w| word_similarity(q, w)).fold(0.0, f64::max))
+        .sum();
+    total / query_words.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_strips_accents_and_case() {
+        assert_eq!(remove_accents("Şükür"), "Sukur");
+        assert_eq!(remove_accents("Džeko"), "Dzeko");
+        assert_eq!(normalize_name("ŁUKASZ Błaszczykowski"), "lukasz blaszczykowski");
+        // Precomposed and decomposed forms normalize the same
+        assert_eq!(normalize_name("Jose\u{301}"), normalize_name("José"));
+    }
+
+    #[test]
+    fn edit_distance_counts_characters() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("łódź", "lodz"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn relevance_ranks_typos_below_exact_matches() {
+        let key = "cristiano ronaldo";
+        assert_eq!(name_relevance(key, "ronaldo"), 1.0);
+        assert_eq!(name_relevance(key, "ronal"), 0.9);
+        let typo = name_relevance(key, "ronaldp");
+        assert!(typo > 0.8 && typo < 0.9);
+        assert!(name_relevance(key, "messi") < typo);
+        assert_eq!(name_relevance(key, ""), 1.0);
+        assert_eq!(name_relevance("", "ronaldo"), 0.0);
     }
 }